@@ -1,8 +1,12 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use cte::chess::board;
+use cte::chess::board_manager::BoardManager;
 
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("board_creation", |b| b.iter(board::Board::new));
+    c.bench_function("board_creation", |b| b.iter(BoardManager::new));
+    c.bench_function("legal_moves_start_position", |b| {
+        let mut board = BoardManager::new();
+        b.iter(|| black_box(board.legal_moves()))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,31 +1,149 @@
 use std::collections::VecDeque;
-use std::error::Error;
-use std::fmt; use std::fmt::Formatter;
+use std::fmt;
+use std::fmt::Formatter;
+use std::rc::Rc;
 
 use array2ds::array2d::Array2d;
 use array2ds::array2d::GridIdx;
 
 use crate::chess::piece::PieceType::King;
 
-use super::parse::{ChessMove, ChessTurn}; use super::piece::{Color, Piece, PieceType, Position};
+use super::chess960;
+use super::error::{FenError, MoveError};
+use super::material::{piece_points, Material};
+use super::parse::{parse_square, position_to_algebraic_notation, ChessMove, ChessTurn};
+use super::piece::{Color, Piece, PieceType, Position};
 use super::piece_movement as pm;
 use super::piece_movement::Velocity;
-
-type MyResult<T> = Result<T, Box<dyn Error>>;
-
-pub struct BoardSizeInfo();
+use super::renderer::{Orientation, Renderer};
+use super::variant::{Variant, VariantKind, CHECKS_TO_WIN, STANDARD_START_FEN};
+
+/// the smallest number of rows or columns a board can have
+pub const MIN_BOARD_SIZE: usize = 5;
+/// the biggest number of rows or columns a board can have, the columns go up to j
+pub const MAX_BOARD_SIZE: usize = 10;
+
+/// the number of rows and columns of the board
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct BoardSizeInfo {
+    rows: usize,
+    columns: usize,
+}
 
 pub struct KingsTracker {
-    pub(super) white_king_pos: Position,
-    pub(super) black_king_pos: Position,
+    pub(super) white_king_pos: Option<Position>,
+    pub(super) black_king_pos: Option<Position>,
 }
 
+/// the king position kept for a color without a king, it is outside the board
+/// so the color never has a king there
+const NO_KING: Position = Position { x: -1, y: -1 };
+
 impl BoardSizeInfo {
-    pub fn row_count() -> usize {
-        8_usize
+    /// the size of the board if both the rows and the columns are between 5 and 10
+    pub fn new(rows: usize, columns: usize) -> Option<Self> {
+        let supported = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+        (supported.contains(&rows) && supported.contains(&columns))
+            .then_some(BoardSizeInfo { rows, columns })
+    }
+
+    /// parse the size written as columns x rows, for example 10x8
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (columns, rows) = name
+            .to_lowercase()
+            .split_once('x')
+            .and_then(|(columns, rows)| Some((columns.parse().ok()?, rows.parse().ok()?)))?;
+        BoardSizeInfo::new(rows, columns)
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns
+    }
+
+    pub fn last_row(&self) -> i8 {
+        self.rows as i8 - 1
+    }
+
+    pub fn last_column(&self) -> i8 {
+        self.columns as i8 - 1
+    }
+
+    /// check if the position is on a board of this size
+    pub fn contains(&self, position: &Position) -> bool {
+        (0..self.rows as i8).contains(&position.x) && (0..self.columns as i8).contains(&position.y)
+    }
+
+    /// the row the pieces of the color start on
+    pub fn back_row(&self, color: Color) -> i8 {
+        match color {
+            Color::White => 0,
+            Color::Black => self.last_row(),
+        }
     }
-    pub fn column_count() -> usize {
-        8_usize
+
+    /// the row the pawns of the color start on
+    pub fn pawn_row(&self, color: Color) -> i8 {
+        self.back_row(color) + pawn_forward_direction(color)
+    }
+
+    /// pawns step two squares from their start row only on boards of 8 rows or more
+    pub fn has_pawn_double_step(&self) -> bool {
+        self.rows >= 8
+    }
+
+    /// the column the kings start on, next to the queen
+    pub fn king_column(&self) -> i8 {
+        self.columns as i8 / 2
+    }
+
+    /// the fen string of the start position for the size, Gardner minichess on 5x5,
+    /// Los Alamos chess on 6x6 and Capablanca chess on 10 columns, other sizes spread rooks,
+    /// knights and bishops from the corners with the queen and the king in the middle
+    pub fn start_fen(&self) -> String {
+        let back_row: String = if self.columns == MIN_BOARD_SIZE {
+            "rnbqk".to_string()
+        } else if self.columns == MAX_BOARD_SIZE {
+            "rnabqkbcnr".to_string()
+        } else {
+            (0..self.columns as i8)
+                .map(|column| match column - self.king_column() {
+                    0 => 'k',
+                    -1 => 'q',
+                    _ => ['r', 'n', 'b'][column.min(self.last_column() - column) as usize % 3],
+                })
+                .collect()
+        };
+        let pawns = "p".repeat(self.columns);
+        let empty_rows = vec![self.columns.to_string(); self.rows - 4].join("/");
+        let castling_field = if self.rows >= 8 { "KQkq" } else { "-" };
+        format!(
+            "{}/{}/{}/{}/{} w {} - 0 1",
+            back_row,
+            pawns,
+            empty_rows,
+            pawns.to_uppercase(),
+            back_row.to_uppercase(),
+            castling_field
+        )
+    }
+}
+
+impl Default for BoardSizeInfo {
+    fn default() -> Self {
+        BoardSizeInfo {
+            rows: 8,
+            columns: 8,
+        }
+    }
+}
+
+impl fmt::Display for BoardSizeInfo {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.columns, self.rows)
     }
 }
 
@@ -51,13 +169,250 @@ impl Square {
     }
 }
 
+/// the state of the game as seen from the board
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate {
+        winner: Color,
+    },
+    Stalemate,
+    /// the winner gave check for the third time in three-check
+    ThirdCheck {
+        winner: Color,
+    },
+    /// the winner has no pieces or no legal moves left in antichess
+    OutOfMoves {
+        winner: Color,
+    },
+    /// the winner captured every white piece in horde
+    AllPiecesCaptured {
+        winner: Color,
+    },
+    /// the king of the winner reached the last row first in racing kings
+    GoalReached {
+        winner: Color,
+    },
+    /// both kings reached the last row in racing kings
+    RaceDrawn,
+    /// the winner brought the king to the center in King of the Hill
+    KingOfTheHill {
+        winner: Color,
+    },
+    /// the winner exploded the enemy king in atomic chess
+    KingExploded {
+        winner: Color,
+    },
+}
+
+impl GameStatus {
+    /// the color that won the game if it ended with a winner
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            GameStatus::Checkmate { winner }
+            | GameStatus::ThirdCheck { winner }
+            | GameStatus::OutOfMoves { winner }
+            | GameStatus::AllPiecesCaptured { winner }
+            | GameStatus::GoalReached { winner }
+            | GameStatus::KingOfTheHill { winner }
+            | GameStatus::KingExploded { winner } => Some(*winner),
+            GameStatus::Ongoing | GameStatus::Stalemate | GameStatus::RaceDrawn => None,
+        }
+    }
+}
+
+/// a piece that can't leave the line between its king and the enemy piece attacking through it
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Pin {
+    pub pinned: Position,
+    pub pinner: Position,
+    /// the squares from the king to the pinner, including the pinner,
+    /// the pinned piece can move only along them
+    pub ray: Vec<Position>,
+}
+
+/// which castling moves are still allowed, lost once the king or the rook moves,
+/// each right keeps the column of the rook the king castles with
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct CastlingRights {
+    pub white_king_side: Option<i8>,
+    pub white_queen_side: Option<i8>,
+    pub black_king_side: Option<i8>,
+    pub black_queen_side: Option<i8>,
+}
+
+impl CastlingRights {
+    /// the column of the rook the color can still castle with to the side
+    pub fn rook_column(&self, color: Color, king_side: bool) -> Option<i8> {
+        match (color, king_side) {
+            (Color::White, true) => self.white_king_side,
+            (Color::White, false) => self.white_queen_side,
+            (Color::Black, true) => self.black_king_side,
+            (Color::Black, false) => self.black_queen_side,
+        }
+    }
+
+    /// check if the color can still castle to the side
+    pub fn allows(&self, color: Color, king_side: bool) -> bool {
+        self.rook_column(color, king_side).is_some()
+    }
+
+    fn rook_column_mut(&mut self, color: Color, king_side: bool) -> &mut Option<i8> {
+        match (color, king_side) {
+            (Color::White, true) => &mut self.white_king_side,
+            (Color::White, false) => &mut self.white_queen_side,
+            (Color::Black, true) => &mut self.black_king_side,
+            (Color::Black, false) => &mut self.black_queen_side,
+        }
+    }
+
+    /// remove both castling rights of the color
+    pub fn remove_color(&mut self, color: Color) {
+        *self.rook_column_mut(color, true) = None;
+        *self.rook_column_mut(color, false) = None;
+    }
+
+    /// remove the castling right of the color that uses the rook on the column
+    pub fn remove_rook(&mut self, color: Color, column: i8) {
+        for king_side in [true, false] {
+            let rook_column = self.rook_column_mut(color, king_side);
+            if *rook_column == Some(column) {
+                *rook_column = None;
+            }
+        }
+    }
+
+    /// check if no castling is allowed at all
+    pub fn is_empty(&self) -> bool {
+        *self == CastlingRights::default()
+    }
+}
+
+/// the number of checks each color gave, counted only in three-check
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct CheckCounters {
+    pub white: u8,
+    pub black: u8,
+}
+
+impl CheckCounters {
+    /// the checks given by the color
+    pub fn given_by(&self, color: Color) -> u8 {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        }
+    }
+
+    fn given_by_mut(&mut self, color: Color) -> &mut u8 {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+}
+
+/// the piece types that can be in a crazyhouse pocket, in the order fen strings list them
+const POCKET_PIECES: [PieceType; 11] = [
+    PieceType::Amazon,
+    PieceType::Chancellor,
+    PieceType::Archbishop,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Camel,
+    PieceType::Zebra,
+    PieceType::Grasshopper,
+    PieceType::Pawn,
+];
+
+/// the pieces a player captured in crazyhouse and can drop on the board
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct Pocket([u8; POCKET_PIECES.len()]);
+
+impl Pocket {
+    fn index(p_type: PieceType) -> usize {
+        POCKET_PIECES
+            .iter()
+            .position(|pocket_type| *pocket_type == p_type)
+            .expect("kings can't be in a pocket")
+    }
+
+    /// how many pieces of the type are in the pocket
+    pub fn count(&self, p_type: PieceType) -> u8 {
+        self.0[Pocket::index(p_type)]
+    }
+
+    /// every piece in the pocket, the same type repeats for each piece of it
+    pub fn pieces(&self) -> Vec<PieceType> {
+        POCKET_PIECES
+            .iter()
+            .flat_map(|p_type| std::iter::repeat_n(*p_type, self.count(*p_type) as usize))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|count| *count == 0)
+    }
+
+    fn add(&mut self, p_type: PieceType) {
+        self.0[Pocket::index(p_type)] += 1;
+    }
+
+    fn remove(&mut self, p_type: PieceType) {
+        self.0[Pocket::index(p_type)] -= 1;
+    }
+}
+
+/// the pockets of both colors
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct Pockets {
+    pub white: Pocket,
+    pub black: Pocket,
+}
+
+impl Pockets {
+    /// the pocket of the color
+    pub fn of(&self, color: Color) -> &Pocket {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    fn of_mut(&mut self, color: Color) -> &mut Pocket {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BoardManager {
     board: Board,
+    size: BoardSizeInfo,
     turns_counter: usize,
     moves_tracker: VecDeque<ChessTurn>,
+    undone_moves: Vec<ChessMove>,
     white_king_pos: Position,
     black_king_pos: Position,
+    side_to_move: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Position>,
+    halfmove_clock: usize,
+    fullmove_number: usize,
+    chess960: bool,
+    variant: Rc<dyn Variant>,
+    checks_given: CheckCounters,
+    pockets: Pockets,
+    /// the squares of the pieces that were pawns before promotion, tracked in crazyhouse
+    promoted: Vec<Position>,
+    /// the fairy pieces the game started with, pawns can promote to them too
+    fairy_types: Vec<PieceType>,
+    orientation: Orientation,
+    renderer: Renderer,
 }
 
 /*impl fmt::Debug for BoardManager {
@@ -85,37 +440,845 @@ impl GridIdx for Position {
 
 impl Default for BoardManager {
     fn default() -> Self {
-        let mut board =
-            Board::filled_with_default(BoardSizeInfo::row_count(), BoardSizeInfo::column_count());
+        let size = BoardSizeInfo::default();
+        let mut board = Board::filled_with_default(size.row_count(), size.column_count());
+
+        let king_tracker = BoardManager::load_default_game_position(&mut board, &size);
+
+        BoardManager::with_board(board, size, king_tracker)
+    }
+}
 
-        let king_tracker = BoardManager::load_default_game_position(&mut board);
+impl Clone for BoardManager {
+    fn clone(&self) -> Self {
+        let mut board = Board::filled_with_default(self.size.row_count(), self.size.column_count());
+        for (index, square) in self.board.iter().enumerate() {
+            board[(
+                index / self.size.column_count(),
+                index % self.size.column_count(),
+            )] = *square;
+        }
 
         BoardManager {
             board,
+            size: self.size,
+            turns_counter: self.turns_counter,
+            moves_tracker: self.moves_tracker.clone(),
+            undone_moves: self.undone_moves.clone(),
+            white_king_pos: self.white_king_pos,
+            black_king_pos: self.black_king_pos,
+            side_to_move: self.side_to_move,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            chess960: self.chess960,
+            variant: Rc::clone(&self.variant),
+            checks_given: self.checks_given,
+            pockets: self.pockets,
+            promoted: self.promoted.clone(),
+            fairy_types: self.fairy_types.clone(),
+            orientation: self.orientation,
+            renderer: self.renderer,
+        }
+    }
+}
+
+impl BoardManager {
+    pub fn new() -> Self {
+        BoardManager::default()
+    }
+
+    /// create the board from fen string, panic if the fen string is not valid
+    pub fn new_from_fen(fen_string: &str) -> Self {
+        BoardManager::try_from_fen(fen_string).unwrap()
+    }
+
+    /// create the board from fen string, only the pieces placement is required,
+    /// missing castling rights are given to kings and rooks on their starting squares,
+    /// a crazyhouse pocket follows the placement in brackets and promoted pieces are marked with ~
+    pub fn try_from_fen(fen_string: &str) -> Result<Self, FenError> {
+        BoardManager::try_from_variant_fen(fen_string, VariantKind::Standard)
+    }
+
+    /// create the board of the variant from fen string, for standard chess the three-check
+    /// counters and the crazyhouse pocket in the fen string choose their variant by themselves,
+    /// any other variant without them is an error
+    pub fn try_from_variant_fen(
+        fen_string: &str,
+        variant: impl Into<Rc<dyn Variant>>,
+    ) -> Result<Self, FenError> {
+        let variant = variant.into();
+        let infers_variant = variant.name() == VariantKind::Standard.name();
+        let mut fen_fields = fen_string.split_whitespace();
+        let placement = fen_fields.next().ok_or(FenError::Empty)?;
+        let (placement, pockets) = match placement.split_once('[') {
+            Some((placement, pockets)) => (placement, Some(parse_pockets(pockets)?)),
+            None => (placement, None),
+        };
+
+        let unmarked_placement = placement.replace('~', "");
+        let size = placement_size(&unmarked_placement)?;
+        let mut board = Board::filled_with_default(size.row_count(), size.column_count());
+        let king_tracker =
+            BoardManager::load_fen_string_to_board(&mut board, &size, &unmarked_placement)?;
+        if variant.requires_king(Color::White) && king_tracker.white_king_pos.is_none() {
+            return Err(FenError::MissingKing(Color::White));
+        }
+        if variant.requires_king(Color::Black) && king_tracker.black_king_pos.is_none() {
+            return Err(FenError::MissingKing(Color::Black));
+        }
+        let mut board_manager = BoardManager::with_board(board, size, king_tracker);
+        board_manager.variant = Rc::clone(&variant);
+        if let Some(pockets) = pockets {
+            if !variant.has_pockets() && !infers_variant {
+                return Err(FenError::UnexpectedPocket);
+            }
+            board_manager.pockets = pockets;
+            board_manager.add_fairy_types(
+                [Color::White, Color::Black]
+                    .iter()
+                    .flat_map(|color| pockets.of(*color).pieces())
+                    .collect(),
+            );
+            board_manager.promoted = promoted_positions(placement, &size);
+            if !variant.has_pockets() {
+                board_manager.variant = VariantKind::Crazyhouse.into();
+            }
+        }
+
+        board_manager.side_to_move = match fen_fields.next() {
+            None | Some("w") => Color::White,
+            Some("b") => Color::Black,
+            Some(_) => return Err(FenError::InvalidSideToMove),
+        };
+
+        if let Some(castling_field) = fen_fields.next() {
+            board_manager.castling_rights = board_manager.parse_castling_field(castling_field)?;
+            board_manager.chess960 = board_manager.has_chess960_castling(castling_field);
+        }
+        if !variant.allows_castling() {
+            board_manager.castling_rights = CastlingRights::default();
+        }
+
+        board_manager.en_passant = match fen_fields.next() {
+            None | Some("-") => None,
+            Some(square) => {
+                let square = parse_square(square).map_err(|_| FenError::InvalidEnPassant)?;
+                // the square a pawn of the side that just moved skipped with its double step
+                let mover = opposite_color(board_manager.side_to_move);
+                let skipped_row = size.pawn_row(mover) + pawn_forward_direction(mover);
+                if !size.has_pawn_double_step()
+                    || !size.contains(&square)
+                    || square.x != skipped_row
+                {
+                    return Err(FenError::InvalidEnPassant);
+                }
+                Some(square)
+            }
+        };
+
+        if let Some(halfmove_clock) = fen_fields.next() {
+            board_manager.halfmove_clock = halfmove_clock
+                .parse()
+                .map_err(|_| FenError::InvalidHalfmoveClock)?;
+        }
+
+        if let Some(fullmove_number) = fen_fields.next() {
+            board_manager.fullmove_number = fullmove_number
+                .parse()
+                .map_err(|_| FenError::InvalidFullmoveNumber)?;
+            if board_manager.fullmove_number == 0 {
+                return Err(FenError::InvalidFullmoveNumber);
+            }
+        }
+
+        if let Some(check_counters) = fen_fields.next() {
+            board_manager.checks_given = parse_check_counters(check_counters)?;
+            if !variant.counts_checks() && !infers_variant {
+                return Err(FenError::UnexpectedCheckCounters);
+            }
+            if !variant.counts_checks() {
+                board_manager.variant = VariantKind::ThreeCheck.into();
+            }
+        }
+
+        Ok(board_manager)
+    }
+
+    /// create the board of the start position of the variant
+    pub fn new_variant(variant: impl Into<Rc<dyn Variant>>) -> Self {
+        let variant = variant.into();
+        BoardManager::try_from_variant_fen(variant.start_fen(), Rc::clone(&variant)).unwrap()
+    }
+
+    /// create the board of the start position for the number of rows and columns
+    pub fn new_sized(size: BoardSizeInfo) -> Self {
+        BoardManager::try_from_fen(&size.start_fen()).unwrap()
+    }
+
+    /// create the board of a Fischer random start position, the index is between 0 and 959
+    pub fn new_chess960(index: usize) -> Option<Self> {
+        let mut board_manager = BoardManager::try_from_fen(&chess960::start_fen(index)?).ok()?;
+        board_manager.chess960 = true;
+        Some(board_manager)
+    }
+
+    /// check if the castling is played by the Fischer random rules,
+    /// castling moves are then written as the king moving to the rook
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
+
+    /// the rules the game is played by
+    pub fn variant(&self) -> &Rc<dyn Variant> {
+        &self.variant
+    }
+
+    /// play the position by the rules of the variant, castling rights are dropped
+    /// if the variant has no castling
+    pub fn set_variant(&mut self, variant: impl Into<Rc<dyn Variant>>) {
+        self.variant = variant.into();
+        if !self.variant.allows_castling() {
+            self.castling_rights = CastlingRights::default();
+        }
+    }
+
+    /// the checks given by each color, counted only in three-check
+    pub fn checks_given(&self) -> CheckCounters {
+        self.checks_given
+    }
+
+    /// the pieces each color can drop, used only in crazyhouse
+    pub fn pockets(&self) -> Pockets {
+        self.pockets
+    }
+
+    /// the winner by the rules of the variant, the game ends even if there are moves left
+    pub fn variant_winner(&mut self) -> Option<Color> {
+        self.variant_status()?.winner()
+    }
+
+    /// the end of the game by the rules of the variant if it was reached,
+    /// the game ends even if there are moves left
+    pub fn variant_status(&mut self) -> Option<GameStatus> {
+        let variant = Rc::clone(&self.variant);
+        variant.game_status(self)
+    }
+
+    /// parse the castling field of fen string, supports KQkq, X-FEN and Shredder-FEN
+    /// where the rook is given by its column letter
+    fn parse_castling_field(&self, castling_field: &str) -> Result<CastlingRights, FenError> {
+        let mut castling_rights = CastlingRights::default();
+        if castling_field == "-" {
+            return Ok(castling_rights);
+        }
+
+        for castling_char in castling_field.chars() {
+            let color = match castling_char.is_ascii_uppercase() {
+                true => Color::White,
+                false => Color::Black,
+            };
+            let king_pos = self.king_position(color);
+            if king_pos.x != self.size.back_row(color) {
+                return Err(FenError::InvalidCastlingRights);
+            }
+
+            let rook_column = match castling_char.to_ascii_lowercase() {
+                'k' => self.outermost_rook_column(color, true),
+                'q' => self.outermost_rook_column(color, false),
+                column_char @ 'a'..='j' => {
+                    let column = (column_char as u8 - b'a') as i8;
+                    let rook = Piece::new(PieceType::Rook, color);
+                    let position = Position::new(king_pos.x, column);
+                    (self.piece_at(&position) == Some(rook)).then_some(column)
+                }
+                _ => None,
+            };
+            let rook_column = rook_column.ok_or(FenError::InvalidCastlingRights)?;
+            let king_side = rook_column > king_pos.y;
+            *castling_rights.rook_column_mut(color, king_side) = Some(rook_column);
+        }
+        Ok(castling_rights)
+    }
+
+    /// the column of the rook furthest from the king on the side of the back row
+    fn outermost_rook_column(&self, color: Color, king_side: bool) -> Option<i8> {
+        let king_pos = self.king_position(color);
+        let rook = Some(Piece::new(PieceType::Rook, color));
+        let is_rook = |column: &i8| self.piece_at(&Position::new(king_pos.x, *column)) == rook;
+        if king_side {
+            (king_pos.y + 1..self.size.column_count() as i8)
+                .rev()
+                .find(is_rook)
+        } else {
+            (0..king_pos.y).find(is_rook)
+        }
+    }
+
+    /// check if the castling rights can only be played by the Fischer random rules
+    fn has_chess960_castling(&self, castling_field: &str) -> bool {
+        if self.castling_rights.is_empty() {
+            return false;
+        }
+        let last_column = self.size.column_count() as i8 - 1;
+        let standard_rook_columns = [None, Some(0), Some(last_column)];
+        let standard_rooks = [true, false].iter().all(|king_side| {
+            [Color::White, Color::Black].iter().all(|color| {
+                standard_rook_columns
+                    .contains(&self.castling_rights.rook_column(*color, *king_side))
+            })
+        });
+        let standard_kings = [Color::White, Color::Black].iter().all(|color| {
+            !self.castling_rights.allows(*color, true)
+                && !self.castling_rights.allows(*color, false)
+                || self.king_position(*color).y == self.size.king_column()
+        });
+        !standard_rooks
+            || !standard_kings
+            || castling_field
+                .chars()
+                .any(|castling_char| !"KQkq".contains(castling_char))
+    }
+
+    /// format the castling rights as the castling field of fen string, X-FEN uses KQkq unless
+    /// another rook stands further on the side, Shredder-FEN always uses the column letters
+    fn castling_field(&self, shredder: bool) -> String {
+        let mut castling_field = String::new();
+        for (color, king_side, side_char) in [
+            (Color::White, true, 'K'),
+            (Color::White, false, 'Q'),
+            (Color::Black, true, 'k'),
+            (Color::Black, false, 'q'),
+        ] {
+            let rook_column = match self.castling_rights.rook_column(color, king_side) {
+                Some(rook_column) => rook_column,
+                None => continue,
+            };
+            let castling_char =
+                if !shredder && self.outermost_rook_column(color, king_side) == Some(rook_column) {
+                    side_char
+                } else {
+                    (b'a' + rook_column as u8) as char
+                };
+            match color {
+                Color::White => castling_field.push(castling_char.to_ascii_uppercase()),
+                Color::Black => castling_field.push(castling_char.to_ascii_lowercase()),
+            }
+        }
+
+        if castling_field.is_empty() {
+            return "-".to_string();
+        }
+        castling_field
+    }
+
+    /// write the move as uci, castling in standard chess is written as the king moving
+    /// two squares and in Fischer random chess as the king moving to the rook
+    pub fn move_to_uci(&self, chess_move: &ChessMove) -> String {
+        if !self.chess960 && self.is_castling(&chess_move.piece_source, &chess_move.piece_dest) {
+            let (king_dest, _) =
+                self.castling_destinations(&chess_move.piece_source, &chess_move.piece_dest);
+            return ChessMove::new(chess_move.piece_source, king_dest, None).to_string();
+        }
+        chess_move.to_string()
+    }
+
+    /// create the board manager for a loaded board, castling is allowed
+    /// for kings and rooks that stand on their starting squares
+    fn with_board(board: Board, size: BoardSizeInfo, king_tracker: KingsTracker) -> Self {
+        let mut board_manager = BoardManager {
+            board,
+            size,
             turns_counter: 0,
             moves_tracker: VecDeque::with_capacity(80),
-            white_king_pos: king_tracker.white_king_pos,
-            black_king_pos: king_tracker.black_king_pos,
+            undone_moves: Vec::new(),
+            white_king_pos: king_tracker.white_king_pos.unwrap_or(NO_KING),
+            black_king_pos: king_tracker.black_king_pos.unwrap_or(NO_KING),
+            side_to_move: Color::White,
+            castling_rights: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            chess960: false,
+            variant: VariantKind::Standard.into(),
+            checks_given: CheckCounters::default(),
+            pockets: Pockets::default(),
+            promoted: Vec::new(),
+            fairy_types: Vec::new(),
+            orientation: Orientation::default(),
+            renderer: Renderer::default(),
+        };
+        board_manager.add_fairy_types(
+            board_manager
+                .board
+                .iter()
+                .filter_map(|square| square.0.map(|piece| piece.p_type))
+                .collect(),
+        );
+
+        let last_row = size.last_row();
+        let last_column = size.last_column();
+        let king_column = size.king_column();
+        let is_piece_at = |board_manager: &BoardManager, row, column, p_type, p_color| {
+            board_manager.piece_at(&Position::new(row, column)) == Some(Piece::new(p_type, p_color))
+        };
+        let white_king = is_piece_at(&board_manager, 0, king_column, King, Color::White);
+        let black_king = is_piece_at(&board_manager, last_row, king_column, King, Color::Black);
+
+        let right = |allowed: bool, column: i8| allowed.then_some(column);
+        board_manager.castling_rights = CastlingRights {
+            white_king_side: right(
+                white_king
+                    && is_piece_at(
+                        &board_manager,
+                        0,
+                        last_column,
+                        PieceType::Rook,
+                        Color::White,
+                    ),
+                last_column,
+            ),
+            white_queen_side: right(
+                white_king && is_piece_at(&board_manager, 0, 0, PieceType::Rook, Color::White),
+                0,
+            ),
+            black_king_side: right(
+                black_king
+                    && is_piece_at(
+                        &board_manager,
+                        last_row,
+                        last_column,
+                        PieceType::Rook,
+                        Color::Black,
+                    ),
+                last_column,
+            ),
+            black_queen_side: right(
+                black_king
+                    && is_piece_at(&board_manager, last_row, 0, PieceType::Rook, Color::Black),
+                0,
+            ),
+        };
+        board_manager
+    }
+
+    /// remember the fairy pieces among the piece types as pieces of the game
+    fn add_fairy_types(&mut self, p_types: Vec<PieceType>) {
+        for p_type in p_types {
+            if p_type.is_fairy() && !self.fairy_types.contains(&p_type) {
+                self.fairy_types.push(p_type);
+            }
+        }
+    }
+
+    /// the fairy pieces of the game, the pawns can promote to them
+    pub fn fairy_types(&self) -> &[PieceType] {
+        &self.fairy_types
+    }
+
+    /// the color of the player that should make the next move
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    /// the number of moves that have been made on the board
+    pub fn turns_counter(&self) -> usize {
+        self.turns_counter
+    }
+
+    /// the castling moves that are still allowed
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    /// the square a pawn passed by moving two squares in the last move
+    pub fn en_passant(&self) -> Option<Position> {
+        self.en_passant
+    }
+
+    /// the number of moves since the last pawn move or capture
+    pub fn halfmove_clock(&self) -> usize {
+        self.halfmove_clock
+    }
+
+    /// the number of the full move, starts at 1 and grows after black moves
+    pub fn fullmove_number(&self) -> usize {
+        self.fullmove_number
+    }
+
+    /// the turns played on the board, oldest first
+    pub fn history(&self) -> &VecDeque<ChessTurn> {
+        &self.moves_tracker
+    }
+
+    /// the pieces the color captured, the enemy pieces its atomic captures exploded included,
+    /// the cheapest first
+    pub fn captured(&self, captor: Color) -> Vec<Piece> {
+        let mut captured: Vec<Piece> = self
+            .moves_tracker
+            .iter()
+            .flat_map(|turn| {
+                // the capturing piece explodes too, on the destination of the move
+                let exploded = turn.exploded.iter().flatten();
+                let by_captor = exploded.clone().any(|(position, piece)| {
+                    *position == turn.chess_move.piece_dest && piece.p_color == captor
+                });
+                let exploded = exploded.filter(move |_| by_captor).map(|(_, piece)| *piece);
+                turn.piece_eaten.into_iter().chain(exploded)
+            })
+            .filter(|piece| piece.p_color != captor)
+            .collect();
+        captured.sort_by_key(|piece| piece_points(piece.p_type));
+        captured
+    }
+
+    /// the pieces of both colors and their points, the pieces in the pockets included
+    pub fn material(&self) -> Material {
+        let mut material = Material::default();
+        for square in self.board.iter() {
+            if let Some(piece) = square.0 {
+                material.of_mut(piece.p_color).add(piece.p_type);
+            }
+        }
+        for color in [Color::White, Color::Black] {
+            for p_type in self.pockets.of(color).pieces() {
+                material.of_mut(color).add(p_type);
+            }
+        }
+        material
+    }
+
+    /// take back the last move, it can be played again with redo
+    pub fn undo(&mut self) -> Result<ChessTurn, MoveError> {
+        if self.moves_tracker.is_empty() {
+            return Err(MoveError::NothingToUndo);
+        }
+        let chess_turn = self.undo_move_regardless();
+        self.undone_moves.push(chess_turn.chess_move);
+        Ok(chess_turn)
+    }
+
+    /// play again the last move that was taken back with undo
+    pub fn redo(&mut self) -> Result<ChessTurn, MoveError> {
+        let chess_move = self.undone_moves.pop().ok_or(MoveError::NothingToRedo)?;
+        self.perform_move(&chess_move)?;
+        Ok(*self.moves_tracker.back().unwrap())
+    }
+
+    /// the piece standing on the position if any
+    pub fn piece_at(&self, position: &Position) -> Option<Piece> {
+        if !self.is_inside_board(position) {
+            return None;
+        }
+        self.board[*position].0
+    }
+
+    /// check if the position is on the board
+    pub fn is_inside_board(&self, position: &Position) -> bool {
+        self.size.contains(position)
+    }
+
+    /// the number of rows and columns of the board
+    pub fn size(&self) -> BoardSizeInfo {
+        self.size
+    }
+
+    /// turn the board around
+    pub fn flip(&mut self) {
+        self.orientation = self.orientation.flipped(self);
+    }
+
+    /// which side of the board is shown at the bottom
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    /// the way the board is drawn as text
+    pub fn renderer(&self) -> Renderer {
+        self.renderer
+    }
+
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+    }
+
+    /// check if the board is shown with the white rows at the bottom
+    pub fn white_in_front(&self) -> bool {
+        self.orientation.bottom(self) == Color::White
+    }
+
+    /// convert the board to fen string, castling rights are written as X-FEN
+    /// and three-check adds the checks given by each color as +white+black
+    pub fn to_fen(&self) -> String {
+        self.fen_with_castling_field(self.castling_field(false))
+    }
+
+    /// convert the board to Shredder-FEN string where castling rights are the rook columns
+    pub fn to_shredder_fen(&self) -> String {
+        self.fen_with_castling_field(self.castling_field(true))
+    }
+
+    fn fen_with_castling_field(&self, castling_field: String) -> String {
+        let mut placement = String::new();
+
+        for (row_number, row_value) in self.board.iter_rows().rev().enumerate() {
+            if row_number > 0 {
+                placement.push('/');
+            }
+            let mut empty_squares = 0;
+            let row = (self.size.row_count() - 1 - row_number) as i8;
+            for (column, square) in row_value.iter().enumerate() {
+                match &square.0 {
+                    Some(piece) => {
+                        if empty_squares > 0 {
+                            placement.push_str(&empty_squares.to_string());
+                            empty_squares = 0;
+                        }
+                        placement.push(piece.to_fen_char());
+                        if self.promoted.contains(&Position::new(row, column as i8)) {
+                            placement.push('~');
+                        }
+                    }
+                    None => empty_squares += 1,
+                }
+            }
+            if empty_squares > 0 {
+                placement.push_str(&empty_squares.to_string());
+            }
+        }
+
+        if self.variant.has_pockets() {
+            placement.push('[');
+            for color in [Color::White, Color::Black] {
+                for p_type in self.pockets.of(color).pieces() {
+                    placement.push(Piece::new(p_type, color).to_fen_char());
+                }
+            }
+            placement.push(']');
+        }
+
+        let side_to_move = match self.side_to_move {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+
+        let en_passant = match &self.en_passant {
+            Some(position) => position_to_algebraic_notation(position),
+            None => "-".to_string(),
+        };
+
+        let mut fen_string = format!(
+            "{} {} {} {} {} {}",
+            placement,
+            side_to_move,
+            castling_field,
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        );
+        if self.variant.counts_checks() {
+            fen_string.push_str(&format!(
+                " +{}+{}",
+                self.checks_given.white, self.checks_given.black
+            ));
+        }
+        fen_string
+    }
+
+    /// all the legal moves of the side to move, the board is left unchanged,
+    /// there are none once the variant decided the game
+    pub fn legal_moves(&mut self) -> Vec<ChessMove> {
+        if self.variant_status().is_some() {
+            return Vec::new();
+        }
+        let try_all = self.must_try_every_move();
+        let pins = self.pinned_pieces(self.side_to_move);
+        let mut legal_moves = Vec::new();
+        for row in 0..self.size.row_count() as i8 {
+            for column in 0..self.size.column_count() as i8 {
+                let src = Position::new(row, column);
+                legal_moves.append(&mut self.generate_legal_moves_from(&src, try_all, &pins));
+            }
+        }
+        if self.variant.has_pockets() {
+            legal_moves.append(&mut self.generate_legal_drops(try_all));
+        }
+        self.variant.filter_moves(self, &mut legal_moves);
+        legal_moves
+    }
+
+    /// check if the move eats a piece, en passant included
+    pub fn is_capture(&self, chess_move: &ChessMove) -> bool {
+        if chess_move.dropped.is_some()
+            || self.is_castling(&chess_move.piece_source, &chess_move.piece_dest)
+        {
+            return false;
+        }
+        match self.piece_at(&chess_move.piece_dest) {
+            Some(_) => true,
+            None => {
+                self.piece_at(&chess_move.piece_source)
+                    .is_some_and(|piece| piece.p_type == PieceType::Pawn)
+                    && chess_move.piece_source.y != chess_move.piece_dest.y
+            }
+        }
+    }
+
+    /// the moves are tried on the board when the king is in check, or when an enemy
+    /// hopper can attack the king over any piece that moves in or out of its line
+    fn must_try_every_move(&self) -> bool {
+        self.is_check(self.side_to_move) || self.faces_hopper(self.side_to_move)
+    }
+
+    /// check if the enemy of the color has a piece that hops over other pieces
+    fn faces_hopper(&self, color: Color) -> bool {
+        self.board.iter().any(|square| {
+            square.0.is_some_and(|piece| {
+                piece.p_color != color
+                    && pm::piece_atoms(&piece.p_type)
+                        .iter()
+                        .any(|atom| atom.reach == pm::Reach::Hop)
+            })
+        })
+    }
+
+    /// generate the drops of the pieces in the pocket of the side to move,
+    /// a drop can't expose the king so it is tried on the board only when it must
+    fn generate_legal_drops(&mut self, try_all: bool) -> Vec<ChessMove> {
+        let mut legal_drops = Vec::new();
+        let mut pocket_types = self.pockets.of(self.side_to_move).pieces();
+        pocket_types.dedup();
+
+        for p_type in pocket_types {
+            let piece = Piece::new(p_type, self.side_to_move);
+            for row in 0..self.size.row_count() as i8 {
+                for column in 0..self.size.column_count() as i8 {
+                    let chess_move = ChessMove::new_drop(p_type, Position::new(row, column));
+                    if self.validate_drop(&chess_move, p_type).is_err() {
+                        continue;
+                    }
+                    if !try_all || self.validate_king_safety(&chess_move, &piece).is_ok() {
+                        legal_drops.push(chess_move);
+                    }
+                }
+            }
         }
+        legal_drops
     }
-}
 
-impl BoardManager {
-    pub fn new() -> Self {
-        BoardManager::default()
+    /// all the legal moves of the piece on the position, the board is left unchanged
+    pub fn legal_moves_from(&mut self, src: &Position) -> Vec<ChessMove> {
+        let mut legal_moves = self.legal_moves();
+        legal_moves
+            .retain(|chess_move| chess_move.dropped.is_none() && chess_move.piece_source == *src);
+        legal_moves
     }
 
-    pub fn new_from_fen(fen_string: &str) -> Self {
-        let mut board =
-            Board::filled_with_default(BoardSizeInfo::row_count(), BoardSizeInfo::column_count());
-        let king_tracker = BoardManager::load_fen_string_to_board(&mut board, fen_string).unwrap();
-        BoardManager {
-            board,
-            turns_counter: 0,
-            moves_tracker: VecDeque::with_capacity(80),
-            white_king_pos: king_tracker.white_king_pos,
-            black_king_pos: king_tracker.black_king_pos,
+    /// the legal moves of the piece by the rules of chess alone, without the variant
+    /// filter and end of the game, for the variant rules that need the moves themselves
+    pub(crate) fn unfiltered_moves_from(&mut self, src: &Position) -> Vec<ChessMove> {
+        let try_all = self.must_try_every_move();
+        let pins = self.pinned_pieces(self.side_to_move);
+        self.generate_legal_moves_from(src, try_all, &pins)
+    }
+
+    /// generate the legal moves of the piece, trying the move on the board only
+    /// when the pins can't tell if the king stays safe
+    fn generate_legal_moves_from(
+        &mut self,
+        src: &Position,
+        try_all: bool,
+        pins: &[Pin],
+    ) -> Vec<ChessMove> {
+        let mut legal_moves = Vec::new();
+        let piece = match self.piece_at(src) {
+            Some(piece) if piece.p_color == self.side_to_move => piece,
+            _ => return legal_moves,
+        };
+
+        let last_row = match piece.p_color {
+            Color::White => self.size.row_count() as i8 - 1,
+            Color::Black => 0,
+        };
+        let pin = pins.iter().find(|pin| pin.pinned == *src);
+        let king_safety = self.variant.has_king_safety();
+        let needs_trial = self.variant.needs_trial_moves();
+
+        for row in 0..self.size.row_count() as i8 {
+            for column in 0..self.size.column_count() as i8 {
+                let dest = Position::new(row, column);
+                let promotions = if piece.p_type == PieceType::Pawn && dest.x == last_row {
+                    [
+                        PieceType::Queen,
+                        PieceType::Rook,
+                        PieceType::Bishop,
+                        PieceType::Knight,
+                        King,
+                    ]
+                    .into_iter()
+                    .chain(self.fairy_types.iter().copied())
+                    .filter(|p_type| self.variant.can_promote_to(*p_type))
+                    .map(Some)
+                    .collect()
+                } else {
+                    vec![None]
+                };
+
+                for promotion in promotions {
+                    let chess_move = ChessMove::new(*src, dest, promotion);
+                    let is_castling = self.is_castling(src, &dest);
+                    if self.validate_movement(&chess_move).is_err() {
+                        continue;
+                    }
+
+                    let is_en_passant = piece.p_type == PieceType::Pawn
+                        && src.y != dest.y
+                        && self.board[dest].is_empty();
+                    let is_legal = if (is_castling && !try_all) || !king_safety {
+                        true
+                    } else if piece.p_type == King || try_all || is_en_passant || needs_trial {
+                        self.validate_king_safety(&chess_move, &piece).is_ok()
+                    } else if let Some(pin) = pin {
+                        pin.ray.contains(&dest)
+                    } else {
+                        true
+                    };
+
+                    if is_legal {
+                        legal_moves.push(chess_move);
+                    }
+                }
+            }
+        }
+        legal_moves
+    }
+
+    /// the status of the game for the side to move, the board is left unchanged
+    pub fn game_status(&mut self) -> GameStatus {
+        if let Some(game_status) = self.variant_status() {
+            return game_status;
+        }
+        if !self.legal_moves().is_empty() {
+            return GameStatus::Ongoing;
+        }
+        if let Some(game_status) = self.variant.status_without_moves(self) {
+            return game_status;
         }
+        if self.is_check(self.side_to_move) {
+            return GameStatus::Checkmate {
+                winner: opposite_color(self.side_to_move),
+            };
+        }
+        GameStatus::Stalemate
     }
 
     /// check if two positions have the same owner
@@ -130,33 +1293,220 @@ impl BoardManager {
         false
     }
 
-    pub fn handle_move(&mut self, chess_move: &ChessMove) -> MyResult<()> {
-        self.perform_move(chess_move)
+    pub fn handle_move(&mut self, chess_move: &ChessMove) -> Result<(), MoveError> {
+        let chess_move = self.normalize_castling(chess_move);
+        self.perform_move(&chess_move)?;
+        self.undone_moves.clear();
+        Ok(())
     }
+    /// check if the king of the color is attacked, never in variants without king safety
+    /// or when the color has no king
     pub fn is_check(&self, king_color: Color) -> bool {
-        /*
-         MAYBE ADD CACHING
-        */
-        let king_pos = match king_color {
+        if !self.variant.has_king_safety() || !self.has_king(king_color) {
+            return false;
+        }
+        let king_pos = self.king_position(king_color);
+        if self.variant.captures_explode() {
+            // capturing a king next to the other king would explode both of them
+            let enemy_color = opposite_color(king_color);
+            let enemy_king_pos = self.king_position(enemy_color);
+            let kings_touch = (king_pos.x - enemy_king_pos.x).abs() <= 1
+                && (king_pos.y - enemy_king_pos.y).abs() <= 1;
+            if self.has_king(enemy_color) && kings_touch {
+                return false;
+            }
+        }
+        self.is_square_attacked(&king_pos, opposite_color(king_color))
+    }
+
+    /// check if the king of the color is still on the board, it can be exploded in atomic chess
+    pub fn has_king(&self, king_color: Color) -> bool {
+        self.piece_at(&self.king_position(king_color)) == Some(Piece::new(King, king_color))
+    }
+
+    /// the position of the king of the color
+    pub fn king_position(&self, king_color: Color) -> Position {
+        match king_color {
             Color::White => self.white_king_pos,
             Color::Black => self.black_king_pos,
-        };
-        for (row_index, row) in self.board.iter_rows().enumerate() {
-            for (column_index, square) in row.iter().enumerate() {
-                if !square.is_empty() && !square.same_owner(king_color) {
-                    let src = Position::new(row_index as i8, column_index as i8);
+        }
+    }
+
+    /// check if any piece of the attacker color attacks the position
+    pub fn is_square_attacked(&self, position: &Position, attacker_color: Color) -> bool {
+        self.attacking_positions(position, attacker_color)
+            .next()
+            .is_some()
+    }
 
-                    let p_type = square.0.unwrap().p_type;
-                    let is_valid = pm::is_valid_move(&p_type, &src, &king_pos);
-                    let is_clear = self.check_dest_path_is_clear(&src, &king_pos);
+    /// the positions of the pieces of the attacker color that attack the position,
+    /// a piece attacks a position even if a piece of its own color stands there
+    pub fn attackers_of(&self, position: &Position, attacker_color: Color) -> Vec<Position> {
+        self.attacking_positions(position, attacker_color).collect()
+    }
+
+    /// the positions of the enemy pieces that give check to the king of the side to move,
+    /// none when the side has no king
+    pub fn checkers(&self) -> Vec<Position> {
+        let king_color = self.side_to_move;
+        if !self.has_king(king_color) {
+            return Vec::new();
+        }
+        self.attackers_of(&self.king_position(king_color), opposite_color(king_color))
+    }
 
-                    if is_valid && is_clear {
-                        return true;
+    /// the pieces of the color that are pinned to their king by an enemy piece riding
+    /// straight or diagonally
+    pub fn pinned_pieces(&self, color: Color) -> Vec<Pin> {
+        let king_pos = self.king_position(color);
+        let mut pins = Vec::new();
+        if !self.has_king(color) {
+            return pins;
+        }
+
+        for (dx, dy) in [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ] {
+            let straight = dx == 0 || dy == 0;
+            let mut ray = Vec::new();
+            let mut pinned = None;
+            let mut position = Position::new(king_pos.x + dx, king_pos.y + dy);
+
+            while self.is_inside_board(&position) {
+                ray.push(position);
+                if let Some(piece) = self.board[position].0 {
+                    if piece.p_color == color {
+                        if pinned.is_some() {
+                            break;
+                        }
+                        pinned = Some(position);
+                    } else {
+                        let direction = if straight { (1, 0) } else { (1, 1) };
+                        let is_slider = pm::piece_atoms(&piece.p_type)
+                            .iter()
+                            .any(|atom| atom.reach == pm::Reach::Ride && atom.step == direction);
+                        if let (Some(pinned), true) = (pinned, is_slider) {
+                            pins.push(Pin {
+                                pinned,
+                                pinner: position,
+                                ray,
+                            });
+                        }
+                        break;
                     }
                 }
+                position = Position::new(position.x + dx, position.y + dy);
             }
         }
-        false
+        pins
+    }
+
+    /// iterate over the pieces of the attacker color that attack the position
+    fn attacking_positions<'a>(
+        &'a self,
+        position: &'a Position,
+        attacker_color: Color,
+    ) -> impl Iterator<Item = Position> + 'a {
+        self.board
+            .iter()
+            .enumerate()
+            .filter(move |(_, square)| square.same_owner(attacker_color))
+            .map(|(index, square)| {
+                let src = Position::new(
+                    (index / self.size.column_count()) as i8,
+                    (index % self.size.column_count()) as i8,
+                );
+                (src, square.0.unwrap())
+            })
+            .filter(move |(src, piece)| self.is_attacking(piece, src, position))
+            .map(|(src, _)| src)
+    }
+
+    /// check if the piece standing on src attacks the target position
+    fn is_attacking(&self, piece: &Piece, src: &Position, target: &Position) -> bool {
+        if src == target {
+            return false;
+        }
+        // the king can't capture when captures explode
+        if piece.p_type == King && self.variant.captures_explode() {
+            return false;
+        }
+        if piece.p_type == PieceType::Pawn {
+            let forward = pawn_forward_direction(piece.p_color);
+            return target.x - src.x == forward && (target.y - src.y).abs() == 1;
+        }
+        if piece.p_type.is_fairy() {
+            return match pm::find_atom(&piece.p_type, src, target) {
+                Some((atom, velocity)) if atom.reach == pm::Reach::Hop => {
+                    self.is_hop_clear(src, &velocity)
+                }
+                Some(_) => self.is_path_between_clear(src, target),
+                None => false,
+            };
+        }
+        pm::is_valid_move(&piece.p_type, src, target) && self.is_path_between_clear(src, target)
+    }
+
+    /// the velocity of the move when the piece hops to its destination
+    fn hop_velocity(&self, piece: &Piece, chess_move: &ChessMove) -> Option<Velocity> {
+        if !piece.p_type.is_fairy() {
+            return None;
+        }
+        match pm::find_atom(
+            &piece.p_type,
+            &chess_move.piece_source,
+            &chess_move.piece_dest,
+        ) {
+            Some((atom, velocity)) if atom.reach == pm::Reach::Hop => Some(velocity),
+            _ => None,
+        }
+    }
+
+    /// check that a hop finds the first piece on its way right before its destination
+    fn is_hop_clear(&self, src: &Position, velocity: &Velocity) -> bool {
+        if velocity.scalar < 2 {
+            return false;
+        }
+        (1..velocity.scalar).all(|step| {
+            let position = Position::new(src.x + velocity.x * step, src.y + velocity.y * step);
+            self.board[position].is_empty() != (step == velocity.scalar - 1)
+        })
+    }
+
+    /// check that the squares between src and dest are empty, not including both of them
+    fn is_path_between_clear(&self, src: &Position, dest: &Position) -> bool {
+        let velocity = Velocity::new(src, dest);
+        let mut curr_pos = Position::new(src.x + velocity.x, src.y + velocity.y);
+
+        while curr_pos != *dest {
+            if !self.board[curr_pos].is_empty() {
+                return false;
+            }
+            curr_pos.x += velocity.x;
+            curr_pos.y += velocity.y;
+        }
+        true
+    }
+
+    /// check that the pawn moves forward, eats only diagonally, or en passant,
+    /// and moves straight only to an empty square
+    fn is_valid_pawn_action(&self, p_color: Color, src: &Position, dest: &Position) -> bool {
+        if (dest.x - src.x).signum() != pawn_forward_direction(p_color) {
+            return false;
+        }
+        let dest_is_empty = self.board[*dest].is_empty();
+        if src.y == dest.y {
+            dest_is_empty
+        } else {
+            !dest_is_empty || self.en_passant == Some(*dest)
+        }
     }
 
     /// check that the move is valid, if piece dest is legal movement if not interrupted by anything
@@ -165,7 +1515,7 @@ impl BoardManager {
         let velocity = Velocity::new(src, dest);
         let mut curr_pos = Position::new(src.x, src.y);
 
-        for _ in 0..BoardSizeInfo::row_count() {
+        for _ in 0..self.size.row_count().max(self.size.column_count()) {
             curr_pos.x += velocity.x;
             curr_pos.y += velocity.y;
 
@@ -207,7 +1557,7 @@ impl BoardManager {
         writeln!(f)?;
 
         for (row_number, row_value) in self.board.iter_rows().rev().enumerate() {
-            write!(f, "{:>2}", BoardSizeInfo::row_count() - row_number).unwrap();
+            write!(f, "{:>2}", self.size.row_count() - row_number).unwrap();
 
             for square in row_value {
                 match &square.0 {
@@ -224,80 +1574,138 @@ impl BoardManager {
 
 impl BoardManager {
     /// load starting position for the chess game
-    fn load_default_game_position(board: &mut Board) -> KingsTracker {
+    fn load_default_game_position(board: &mut Board, size: &BoardSizeInfo) -> KingsTracker {
         //cccccccccccccccccccccccccccccccc:rnbq1bnr/Kpppp1p1/8/8/8/8/1PPPP1Pk/RNBQ1BNR
-        let initial_game_position = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
-        BoardManager::load_fen_string_to_board(board, initial_game_position).unwrap()
+        let initial_game_position = STANDARD_START_FEN.split(' ').next().unwrap();
+        BoardManager::load_fen_string_to_board(board, size, initial_game_position).unwrap()
     }
 
     /// load fen string to the board
-    fn load_fen_string_to_board(board: &mut Board, fen_string: &str) -> MyResult<KingsTracker> {
+    fn load_fen_string_to_board(
+        board: &mut Board,
+        size: &BoardSizeInfo,
+        fen_string: &str,
+    ) -> Result<KingsTracker, FenError> {
         let mut black_king_pos = None;
         let mut white_king_pos = None;
+        if fen_string.split('/').count() != size.row_count() {
+            return Err(FenError::WrongRowCount);
+        }
         for (line_number, line_fen_value) in fen_string.split('/').enumerate() {
+            let row = size.last_row() - line_number as i8;
             let mut current_column_index: usize = 0;
 
-            for fen_value in line_fen_value.chars() {
-                if fen_value.is_numeric() {
-                    let fen_value = fen_value.to_digit(10).unwrap() as usize;
-
-                    for _ in 0..fen_value {
-                        board[(
-                            BoardSizeInfo::row_count() - 1 - line_number,
-                            current_column_index,
-                        )] = Square::default();
-                        current_column_index += 1;
+            for fen_item in fen_row_items(line_fen_value) {
+                let row_width = match fen_item {
+                    FenRowItem::Empty(empty_squares) => empty_squares,
+                    FenRowItem::Piece(_) => 1,
+                };
+                if current_column_index + row_width > size.column_count() {
+                    return Err(FenError::RowTooLong);
+                }
+                match fen_item {
+                    FenRowItem::Empty(empty_squares) => {
+                        for _ in 0..empty_squares {
+                            board[Position::new(row, current_column_index as i8)] =
+                                Square::default();
+                            current_column_index += 1;
+                        }
                     }
-                } else if fen_value.is_ascii_alphabetic() {
-                    let p_type = fen_value.into();
-                    let p_color = fen_value.into();
-                    let p_position = Position::new(
-                        (BoardSizeInfo::row_count() - 1 - line_number) as i8,
-                        current_column_index as i8,
-                    );
-                    match (p_type, p_color) {
-                        (King, Color::White) => white_king_pos = Some(p_position),
-                        (King, Color::Black) => black_king_pos = Some(p_position),
-                        _ => {}
+                    FenRowItem::Piece(fen_value) => {
+                        let piece = Piece::try_from(fen_value)?;
+                        let p_position = Position::new(row, current_column_index as i8);
+                        match (piece.p_type, piece.p_color) {
+                            (King, Color::White) => white_king_pos = Some(p_position),
+                            (King, Color::Black) => black_king_pos = Some(p_position),
+                            _ => {}
+                        }
+                        board[p_position] = Square::new(piece);
+                        current_column_index += 1;
                     }
-                    board[(
-                        BoardSizeInfo::row_count() - (line_number + 1),
-                        current_column_index, // note: fixes pices shifted to the right, need to check why
-                    )] = Square::new(Piece::new(p_type, p_color));
-                    current_column_index += 1;
                 }
             }
+            if current_column_index != size.column_count() {
+                return Err(FenError::RowTooShort);
+            }
         }
 
-        if white_king_pos.is_none() {
-            return Err("White King Not Found")?;
-        }
-        if black_king_pos.is_none() {
-            return Err("Black King Not Found")?;
-        }
-
-        let white_king_pos = white_king_pos.unwrap();
-        let black_king_pos = black_king_pos.unwrap();
         Ok(KingsTracker {
             white_king_pos,
             black_king_pos,
         })
     }
 
-    /// check if castling legal, if the rook didnt move and also the king
-    /// if each of them doesnt appear is any previous move as dest/src
-    fn is_castling_legal(&self, src: &Position, dest: &Position) -> bool {
-        for cm in &self.moves_tracker {
-            let cmp = &cm.chess_move.piece_dest;
-            let cms = &cm.chess_move.piece_source;
-            if src == cmp || src == cms || dest == cmp || dest == cms {
-                return false;
+    /// convert a king moving two squares sideways, like e1g1, to the king moving to the rook,
+    /// in Fischer random chess castling is always written as the king moving to the rook
+    fn normalize_castling(&self, chess_move: &ChessMove) -> ChessMove {
+        let src = chess_move.piece_source;
+        let dest = chess_move.piece_dest;
+        let king = match self.piece_at(&src) {
+            Some(piece) if piece.p_type == King => piece,
+            _ => return *chess_move,
+        };
+
+        if self.chess960 || src.x != dest.x || (dest.y - src.y).abs() < 2 {
+            return *chess_move;
+        }
+
+        let rook_column = self
+            .castling_rights
+            .rook_column(king.p_color, dest.y > src.y);
+        if let Some(rook_column) = rook_column {
+            let rook_pos = Position::new(src.x, rook_column);
+            let (king_dest, _) = self.castling_destinations(&src, &rook_pos);
+            if king_dest == dest && self.is_castling(&src, &rook_pos) {
+                return ChessMove::new(src, rook_pos, chess_move.prompted);
             }
         }
-        true
+        *chess_move
+    }
+
+    /// check that the king and the rook didnt move, that the squares between them are empty
+    /// and that the king doesnt castle out of, through or into check
+    fn validate_castling(&mut self, chess_move: &ChessMove) -> Result<(), MoveError> {
+        let king_pos = chess_move.piece_source;
+        let rook_pos = chess_move.piece_dest;
+        let king_color = self.board[king_pos].0.unwrap().p_color;
+
+        if chess_move.prompted.is_some() {
+            return Err(MoveError::BadPromotion);
+        }
+
+        let back_row = self.size.back_row(king_color);
+        let king_side = rook_pos.y > king_pos.y;
+        let has_right = self.castling_rights.rook_column(king_color, king_side) == Some(rook_pos.y);
+
+        if king_pos.x != back_row || rook_pos.x != back_row || !has_right {
+            return Err(MoveError::CastlingNotAllowed);
+        }
+
+        let (king_dest, rook_dest) = self.castling_destinations(&king_pos, &rook_pos);
+        let columns = [king_pos.y, rook_pos.y, king_dest.y, rook_dest.y];
+        let first_column = *columns.iter().min().unwrap();
+        let last_column = *columns.iter().max().unwrap();
+
+        for column in first_column..=last_column {
+            let position = Position::new(back_row, column);
+            if position != king_pos && position != rook_pos && self.piece_at(&position).is_some() {
+                return Err(MoveError::CastlingBlocked);
+            }
+        }
+
+        let king_path = king_pos.y.min(king_dest.y)..=king_pos.y.max(king_dest.y);
+        for column in king_path {
+            let position = Position::new(back_row, column);
+            if self.is_square_attacked(&position, opposite_color(king_color)) {
+                return Err(MoveError::CastlingThroughCheck);
+            }
+        }
+
+        let king = self.board[king_pos].0.unwrap();
+        self.validate_king_safety(chess_move, &king)
     }
 
-    /// check if the source if king the the desk is rook
+    /// check if the source if king the the desk is rook of the same color
     fn is_castling(&self, src: &Position, dest: &Position) -> bool {
         let piece_source = self.board[*src];
         let piece_dest = self.board[*dest];
@@ -308,134 +1716,558 @@ impl BoardManager {
         let piece_source = piece_source.0.unwrap();
         let piece_dest = piece_dest.0.unwrap();
 
-        if piece_source.p_type == PieceType::King && piece_dest.p_type == PieceType::Rook {
-            return true;
+        piece_source.p_type == PieceType::King
+            && piece_dest.p_type == PieceType::Rook
+            && piece_source.p_color == piece_dest.p_color
+    }
+
+    fn validate_move(&mut self, chess_move: &ChessMove) -> Result<Piece, MoveError> {
+        let piece_source = self.validate_movement(chess_move)?;
+        let variant = Rc::clone(&self.variant);
+        variant.validate_move(self, chess_move)?;
+        let is_castling = self.is_castling(&chess_move.piece_source, &chess_move.piece_dest);
+        let hopper_danger = is_castling && self.faces_hopper(piece_source.p_color);
+        if (!is_castling || hopper_danger) && self.variant.has_king_safety() {
+            self.validate_king_safety(chess_move, &piece_source)?;
         }
-        false
+        Ok(piece_source)
     }
 
-    fn validate_move(&mut self, chess_move: &ChessMove) -> MyResult<Piece> {
-        let piece_source = &self.board[chess_move.piece_source].0;
-        piece_source.ok_or("Illegal Move, Can't Move An Empty Square")?;
-        // todo! need to check if castling is being done, also check if rook and king didnt move before
-        // maybe a simple hashmap/array to track if king/rook used
-        // to do an if dest owner == source owner then check if castling if so check legallty + in check
+    /// check everything but the safety of the king, castling is checked completely
+    fn validate_movement(&mut self, chess_move: &ChessMove) -> Result<Piece, MoveError> {
+        if !self.is_inside_board(&chess_move.piece_source)
+            || !self.is_inside_board(&chess_move.piece_dest)
+        {
+            return Err(MoveError::OutsideBoard);
+        }
+        if let Some(p_type) = chess_move.dropped {
+            return self.validate_drop(chess_move, p_type);
+        }
+        let piece_source = self.board[chess_move.piece_source]
+            .0
+            .ok_or(MoveError::EmptySource)?;
+
+        if piece_source.p_color != self.side_to_move {
+            return Err(MoveError::WrongSide);
+        }
+
+        if chess_move.piece_source == chess_move.piece_dest {
+            return Err(MoveError::NotMoving);
+        }
+
+        if self.is_castling(&chess_move.piece_source, &chess_move.piece_dest) {
+            self.validate_castling(chess_move)?;
+            return Ok(piece_source);
+        }
 
         if self.same_owner(&chess_move.piece_source, &chess_move.piece_dest) {
-            Err("Can't Eat The Same Color")?;
+            return Err(MoveError::OwnPieceCapture);
+        }
+
+        if self.variant.captures_explode()
+            && piece_source.p_type == King
+            && self.piece_at(&chess_move.piece_dest).is_some()
+        {
+            return Err(MoveError::KingCapture);
         }
-        let piece_source = piece_source.unwrap();
         let valid_move = pm::is_valid_move(
             &piece_source.p_type,
             &chess_move.piece_source,
             &chess_move.piece_dest,
-        );
+        ) && !self.is_forbidden_double_step(&piece_source, chess_move);
 
         if !valid_move {
-            Err("Piece Can't Move That Way")?;
+            return Err(MoveError::IllegalPattern);
+        }
+
+        if piece_source.p_type == PieceType::Pawn
+            && !self.is_valid_pawn_action(
+                piece_source.p_color,
+                &chess_move.piece_source,
+                &chess_move.piece_dest,
+            )
+        {
+            return Err(MoveError::IllegalPawnMove);
         }
 
         let prompted = chess_move.prompted.is_some();
 
         if prompted && piece_source.p_type != PieceType::Pawn {
-            Err("Only Pawn Can Be Promoted")?;
+            return Err(MoveError::BadPromotion);
+        }
+
+        if chess_move.prompted.is_some_and(|p_type| {
+            !self.variant.can_promote_to(p_type)
+                || (p_type.is_fairy() && !self.fairy_types.contains(&p_type))
+        }) {
+            return Err(MoveError::BadPromotion);
         }
 
         let in_last_respective_row =
-            [0, BoardSizeInfo::row_count() - 1].contains(&chess_move.piece_dest.no_row());
+            [0, self.size.row_count() - 1].contains(&chess_move.piece_dest.no_row());
 
         if prompted && !in_last_respective_row {
-            Err("Pawn Can Be Promoted Only At The last Respective Row")?;
+            return Err(MoveError::BadPromotion);
+        }
+
+        if !prompted && piece_source.p_type == PieceType::Pawn && in_last_respective_row {
+            return Err(MoveError::MissingPromotion);
+        }
+
+        let path_is_clear = match self.hop_velocity(&piece_source, chess_move) {
+            Some(velocity) => self.is_hop_clear(&chess_move.piece_source, &velocity),
+            None => self.check_dest_path_is_clear(&chess_move.piece_source, &chess_move.piece_dest),
+        };
+        if !path_is_clear {
+            return Err(MoveError::PathBlocked);
+        }
+        Ok(piece_source)
+    }
+
+    /// a pawn moves two squares only from its start row on boards of 8 rows or more,
+    /// and in horde from the first row too
+    fn is_forbidden_double_step(&self, piece: &Piece, chess_move: &ChessMove) -> bool {
+        let src = chess_move.piece_source;
+        if piece.p_type != PieceType::Pawn || (chess_move.piece_dest.x - src.x).abs() != 2 {
+            return false;
         }
+        let from_pawn_row =
+            self.size.has_pawn_double_step() && src.x == self.size.pawn_row(piece.p_color);
+        let from_first_row =
+            self.variant.has_first_row_double_step() && src.x == self.size.back_row(piece.p_color);
+        !from_pawn_row && !from_first_row
+    }
 
-        if !self.check_dest_path_is_clear(&chess_move.piece_source, &chess_move.piece_dest) {
-            Err("That Piece Movement Path Is Blocked")?;
+    /// check that the piece can be dropped from the pocket of the side to move on the square
+    fn validate_drop(&self, chess_move: &ChessMove, p_type: PieceType) -> Result<Piece, MoveError> {
+        let dest = chess_move.piece_dest;
+        if !self.variant.has_pockets() {
+            return Err(MoveError::DropNotAllowed);
+        }
+        if self.pockets.of(self.side_to_move).count(p_type) == 0 {
+            return Err(MoveError::NotInPocket);
         }
+        if self.piece_at(&dest).is_some() {
+            return Err(MoveError::DropOnOccupied);
+        }
+        if p_type == PieceType::Pawn && (dest.x == 0 || dest.x == self.size.row_count() as i8 - 1) {
+            return Err(MoveError::PawnDropOnBackRank);
+        }
+        Ok(Piece::new(p_type, self.side_to_move))
+    }
 
+    /// try the move on the board and check that it doesnt leave the king in check,
+    /// when captures explode taking the enemy king is allowed even when in check
+    fn validate_king_safety(
+        &mut self,
+        chess_move: &ChessMove,
+        piece: &Piece,
+    ) -> Result<(), MoveError> {
+        let enemy_color = opposite_color(piece.p_color);
+        let had_king = self.has_king(piece.p_color);
+        let enemy_had_king = self.has_king(enemy_color);
         self.do_move_regardless(chess_move);
 
-        let is_check = self.is_check(piece_source.p_color);
+        let king_safety = if had_king && !self.has_king(piece.p_color) {
+            Err(MoveError::ExplodesOwnKing)
+        } else if enemy_had_king && !self.has_king(enemy_color) {
+            Ok(())
+        } else if self.is_check(piece.p_color) {
+            Err(MoveError::LeavesKingInCheck)
+        } else {
+            self.variant.validate_position(self, piece.p_color)
+        };
 
         self.undo_move_regardless();
-
-        if is_check {
-            Err("Can't Make A Move That Danger The King")?;
-        }
-        Ok(piece_source)
+        king_safety
     }
 
-    fn perform_move(&mut self, chess_move: &ChessMove) -> MyResult<()> {
+    fn perform_move(&mut self, chess_move: &ChessMove) -> Result<(), MoveError> {
         self.validate_move(chess_move)?;
 
         self.do_move_regardless(chess_move);
-
-        self.turns_counter += 1;
         Ok(())
     }
 
     /// undo any last move that have been done by regardless
-    fn undo_move_regardless(&mut self) {
+    pub(crate) fn undo_move_regardless(&mut self) -> ChessTurn {
         let last_move = self.moves_tracker.pop_back().unwrap();
+        self.turns_counter -= 1;
+        self.side_to_move = opposite_color(self.side_to_move);
+        if self.side_to_move == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        self.castling_rights = last_move.castling_rights;
+        self.en_passant = last_move.en_passant_square;
+        self.halfmove_clock = last_move.halfmove_clock;
+        if last_move.gave_check {
+            *self.checks_given.given_by_mut(self.side_to_move) -= 1;
+        }
         let chess_move = last_move.chess_move;
 
-        let prompted = chess_move.prompted;
+        if let Some(p_type) = chess_move.dropped {
+            self.board[chess_move.piece_dest].0 = None;
+            self.pockets.of_mut(self.side_to_move).add(p_type);
+            return last_move;
+        }
+        if self.variant.has_pockets() {
+            self.untrack_crazyhouse_move(&last_move);
+        }
 
-        self.board[chess_move.piece_source].0 = last_move.piece_eaten;
+        if last_move.castling {
+            let (king_dest, rook_dest) =
+                self.castling_destinations(&chess_move.piece_source, &chess_move.piece_dest);
+            let king = self.board[king_dest].0.take();
+            let rook = self.board[rook_dest].0.take();
+            self.board[chess_move.piece_source].0 = king;
+            self.board[chess_move.piece_dest].0 = rook;
+            self.set_king_position(king.unwrap().p_color, chess_move.piece_source);
+            return last_move;
+        }
 
-        if prompted.is_some() {
+        for (position, piece) in last_move.exploded.iter().flatten() {
+            self.board[*position].0 = Some(*piece);
+        }
+        if chess_move.prompted.is_some() {
             self.board[chess_move.piece_dest].0.as_mut().unwrap().p_type = PieceType::Pawn;
         }
         self.board.swap(
             &last_move.chess_move.piece_source,
             &last_move.chess_move.piece_dest,
         );
-    }
+        self.board[last_move.eaten_at()].0 = last_move.piece_eaten;
 
-    /// make a move even if not legal
-    fn do_move_regardless(&mut self, chess_move: &ChessMove) {
         let piece_source = self.board[chess_move.piece_source].0.unwrap();
-        match (piece_source.p_type, piece_source.p_color) {
-            (King, Color::White) => self.white_king_pos = chess_move.piece_dest,
-            (King, Color::Black) => self.black_king_pos = chess_move.piece_dest,
-            _ => {}
+        if piece_source.p_type == King {
+            self.set_king_position(piece_source.p_color, chess_move.piece_source);
+        }
+        last_move
+    }
+
+    /// the squares the king and the rook land on when the king castles with the rook,
+    /// next to the corner the king lands one column away from it
+    fn castling_destinations(
+        &self,
+        king_pos: &Position,
+        rook_pos: &Position,
+    ) -> (Position, Position) {
+        if rook_pos.y > king_pos.y {
+            let king_column = self.size.last_column() - 1;
+            (
+                Position::new(king_pos.x, king_column),
+                Position::new(king_pos.x, king_column - 1),
+            )
+        } else {
+            (Position::new(king_pos.x, 2), Position::new(king_pos.x, 3))
+        }
+    }
+
+    /// keep track of the king position of the color
+    fn set_king_position(&mut self, king_color: Color, position: Position) {
+        match king_color {
+            Color::White => self.white_king_pos = position,
+            Color::Black => self.black_king_pos = position,
+        }
+    }
+
+    /// the king moving loses both castling rights, a rook moving or being eaten
+    /// loses the castling right of its side
+    fn update_castling_rights(&mut self, piece: &Piece, src: &Position, dest: &Position) {
+        if piece.p_type == King {
+            self.castling_rights.remove_color(piece.p_color);
+        }
+
+        for position in [src, dest] {
+            for color in [Color::White, Color::Black] {
+                if position.x == self.size.back_row(color) {
+                    self.castling_rights.remove_rook(color, position.y);
+                }
+            }
         }
+    }
 
-        let chess_turn = ChessTurn {
+    /// make a move even if not legal
+    pub(crate) fn do_move_regardless(&mut self, chess_move: &ChessMove) {
+        let src = chess_move.piece_source;
+        let dest = chess_move.piece_dest;
+        let mut chess_turn = ChessTurn {
             chess_move: *chess_move,
-            piece_eaten: self.board[chess_move.piece_dest].0.take(),
+            piece_eaten: None,
+            castling: false,
+            en_passant: false,
+            castling_rights: self.castling_rights,
+            en_passant_square: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            gave_check: false,
+            exploded: [None; 9],
+            eaten_promoted: false,
         };
 
-        if chess_move.prompted.is_some() {
-            let replace_pawn_with = chess_move.prompted.unwrap();
-            self.board[chess_move.piece_source] =
-                Square::new(Piece::new(replace_pawn_with, piece_source.p_color))
+        let mover = self.side_to_move;
+        if self.side_to_move == Color::Black {
+            self.fullmove_number += 1;
         }
-        self.board
-            .swap(&chess_move.piece_source, &chess_move.piece_dest);
+        self.turns_counter += 1;
+        self.side_to_move = opposite_color(self.side_to_move);
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+
+        if let Some(p_type) = chess_move.dropped {
+            self.board[dest] = Square::new(Piece::new(p_type, mover));
+            self.pockets.of_mut(mover).remove(p_type);
+            self.count_check(mover, &mut chess_turn);
+            self.moves_tracker.push_back(chess_turn);
+            return;
+        }
+
+        let piece_source = self.board[src].0.unwrap();
+        self.update_castling_rights(&piece_source, &src, &dest);
+
+        if self.is_castling(&src, &dest) {
+            let (king_dest, rook_dest) = self.castling_destinations(&src, &dest);
+            let king = self.board[src].0.take();
+            let rook = self.board[dest].0.take();
+            self.board[king_dest].0 = king;
+            self.board[rook_dest].0 = rook;
+            self.set_king_position(piece_source.p_color, king_dest);
+            chess_turn.castling = true;
+            self.count_check(mover, &mut chess_turn);
+            self.moves_tracker.push_back(chess_turn);
+            return;
+        }
+
+        if piece_source.p_type == King {
+            self.set_king_position(piece_source.p_color, dest);
+        }
+
+        if piece_source.p_type == PieceType::Pawn {
+            self.halfmove_clock = 0;
+            // the first row double step of horde can't be taken en passant
+            if (dest.x - src.x).abs() == 2 && src.x != self.size.back_row(piece_source.p_color) {
+                self.en_passant = Some(Position::new((src.x + dest.x) / 2, src.y));
+            }
+            chess_turn.en_passant = src.y != dest.y && self.board[dest].is_empty();
+        }
+
+        chess_turn.piece_eaten = self.board[chess_turn.eaten_at()].0.take();
+        if chess_turn.piece_eaten.is_some() {
+            self.halfmove_clock = 0;
+        }
+        if self.variant.has_pockets() {
+            self.track_crazyhouse_move(mover, &mut chess_turn);
+        }
+
+        if let Some(replace_pawn_with) = chess_move.prompted {
+            self.board[src] = Square::new(Piece::new(replace_pawn_with, piece_source.p_color))
+        }
+        self.board.swap(&src, &dest);
+        if self.variant.captures_explode() && chess_turn.piece_eaten.is_some() {
+            self.explode(&dest, &mut chess_turn);
+        }
+        self.count_check(mover, &mut chess_turn);
         self.moves_tracker.push_back(chess_turn)
     }
+
+    /// remove the capturing piece and every piece but the pawns around the capture square
+    fn explode(&mut self, center: &Position, chess_turn: &mut ChessTurn) {
+        let mut exploded = chess_turn.exploded.iter_mut();
+        for row in center.x - 1..=center.x + 1 {
+            for column in center.y - 1..=center.y + 1 {
+                let position = Position::new(row, column);
+                match self.piece_at(&position) {
+                    Some(piece) if position == *center || piece.p_type != PieceType::Pawn => {
+                        *exploded.next().unwrap() = Some((position, piece));
+                        self.board[position].0 = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// the eaten piece goes to the pocket of the mover, as a pawn if it was promoted,
+    /// and the promoted pieces are followed to their new squares
+    fn track_crazyhouse_move(&mut self, mover: Color, chess_turn: &mut ChessTurn) {
+        let chess_move = chess_turn.chess_move;
+        if let Some(piece_eaten) = chess_turn.piece_eaten {
+            let eaten_at = chess_turn.eaten_at();
+            chess_turn.eaten_promoted = self.promoted.contains(&eaten_at);
+            self.promoted.retain(|position| *position != eaten_at);
+            let p_type = if chess_turn.eaten_promoted {
+                PieceType::Pawn
+            } else {
+                piece_eaten.p_type
+            };
+            self.pockets.of_mut(mover).add(p_type);
+        }
+
+        if chess_move.prompted.is_some() {
+            self.promoted.push(chess_move.piece_dest);
+        } else if let Some(position) = self
+            .promoted
+            .iter_mut()
+            .find(|position| **position == chess_move.piece_source)
+        {
+            *position = chess_move.piece_dest;
+        }
+    }
+
+    /// reverse the pocket and the promoted pieces changes of the turn, the mover is the side to move
+    fn untrack_crazyhouse_move(&mut self, chess_turn: &ChessTurn) {
+        let chess_move = chess_turn.chess_move;
+        if chess_move.prompted.is_some() {
+            self.promoted
+                .retain(|position| *position != chess_move.piece_dest);
+        } else if let Some(position) = self
+            .promoted
+            .iter_mut()
+            .find(|position| **position == chess_move.piece_dest)
+        {
+            *position = chess_move.piece_source;
+        }
+
+        if let Some(piece_eaten) = chess_turn.piece_eaten {
+            let p_type = if chess_turn.eaten_promoted {
+                self.promoted.push(chess_turn.eaten_at());
+                PieceType::Pawn
+            } else {
+                piece_eaten.p_type
+            };
+            self.pockets.of_mut(self.side_to_move).remove(p_type);
+        }
+    }
+
+    /// count the check the mover just gave, when the variant counts checks
+    fn count_check(&mut self, mover: Color, chess_turn: &mut ChessTurn) {
+        if self.variant.counts_checks() && self.is_check(opposite_color(mover)) {
+            *self.checks_given.given_by_mut(mover) += 1;
+            chess_turn.gave_check = true;
+        }
+    }
+}
+
+/// the color of the other player
+pub fn opposite_color(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
 }
 
-/// format algebraic notation alphabetic
-pub fn algebraic_notation_letters_formatted(f: &mut Formatter) {
+/// parse the crazyhouse pocket of fen string, the part after the [ of the placement
+fn parse_pockets(pockets_field: &str) -> Result<Pockets, FenError> {
+    let pieces = pockets_field
+        .strip_suffix(']')
+        .ok_or(FenError::InvalidPocket)?;
+    let mut pockets = Pockets::default();
+    for piece_char in pieces.chars() {
+        match Piece::try_from(piece_char) {
+            Ok(piece) if piece.p_type != King => pockets.of_mut(piece.p_color).add(piece.p_type),
+            _ => return Err(FenError::InvalidPocket),
+        }
+    }
+    Ok(pockets)
+}
+
+/// the positions of the pieces marked as promoted with ~ in the fen placement
+fn promoted_positions(placement: &str, size: &BoardSizeInfo) -> Vec<Position> {
+    let mut promoted = Vec::new();
+    for (line_number, line_fen_value) in placement.split('/').enumerate() {
+        let row = size.last_row() - line_number as i8;
+        let mut column = 0;
+        for fen_item in fen_row_items(line_fen_value) {
+            match fen_item {
+                FenRowItem::Piece('~') => promoted.push(Position::new(row, column - 1)),
+                FenRowItem::Piece(_) => column += 1,
+                FenRowItem::Empty(empty_squares) => column += empty_squares as i8,
+            }
+        }
+    }
+    promoted
+}
+
+/// a run of empty squares or a single character of a fen placement row
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum FenRowItem {
+    Empty(usize),
+    Piece(char),
+}
+
+/// split the fen placement row to its items, the runs of empty squares
+/// can have two digits on boards wider than 9 columns
+fn fen_row_items(line_fen_value: &str) -> Vec<FenRowItem> {
+    let mut items = Vec::new();
+    for fen_value in line_fen_value.chars() {
+        match (fen_value.to_digit(10), items.last_mut()) {
+            (Some(digit), Some(FenRowItem::Empty(empty_squares))) => {
+                *empty_squares = *empty_squares * 10 + digit as usize;
+            }
+            (Some(digit), _) => items.push(FenRowItem::Empty(digit as usize)),
+            (None, _) => items.push(FenRowItem::Piece(fen_value)),
+        }
+    }
+    items
+}
+
+/// the size of the board described by the fen placement, the width of the first row
+/// gives the columns and every other row is checked against it when loaded
+fn placement_size(placement: &str) -> Result<BoardSizeInfo, FenError> {
+    let rows = placement.split('/').count();
+    let first_row = placement.split('/').next().unwrap_or_default();
+    let columns = fen_row_items(first_row)
+        .iter()
+        .map(|fen_item| match fen_item {
+            FenRowItem::Empty(empty_squares) => *empty_squares,
+            FenRowItem::Piece(_) => 1,
+        })
+        .sum();
+    if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&rows) {
+        return Err(FenError::WrongRowCount);
+    }
+    match BoardSizeInfo::new(rows, columns) {
+        Some(size) => Ok(size),
+        None if columns > MAX_BOARD_SIZE => Err(FenError::RowTooLong),
+        None => Err(FenError::RowTooShort),
+    }
+}
+
+/// parse the three-check fen suffix +white+black with the checks given by each color
+fn parse_check_counters(check_counters: &str) -> Result<CheckCounters, FenError> {
+    let counters: Vec<&str> = check_counters.split('+').collect();
+    match counters.as_slice() {
+        ["", white, black] => {
+            let parse_counter = |counter: &str| match counter.parse() {
+                Ok(checks) if checks <= CHECKS_TO_WIN => Ok(checks),
+                _ => Err(FenError::InvalidCheckCounters),
+            };
+            Ok(CheckCounters {
+                white: parse_counter(white)?,
+                black: parse_counter(black)?,
+            })
+        }
+        _ => Err(FenError::InvalidCheckCounters),
+    }
+}
+
+/// the row direction the pawns of the color are moving to
+fn pawn_forward_direction(color: Color) -> i8 {
+    match color {
+        Color::White => 1,
+        Color::Black => -1,
+    }
+}
+
+/// format algebraic notation alphabetic, a letter for every column of the board
+pub fn algebraic_notation_letters_formatted(f: &mut Formatter, size: &BoardSizeInfo) {
     write!(f, "{:>2}", " ").unwrap();
 
-    for c in 'a'..='h' {
+    for c in (b'a'..b'a' + size.column_count() as u8).map(char::from) {
         write!(f, "{:>2}", c).unwrap();
     }
 }
 
 impl fmt::Display for BoardManager {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        algebraic_notation_letters_formatted(f);
-
-        if self.turns_counter % 2 == 0 {
-            self.output_white_front(f)?;
-        } else {
-            self.output_black_front(f)?;
-        }
-
-        algebraic_notation_letters_formatted(f);
-        write!(f, "")
+        self.renderer.write_board(self, self.orientation, f)
     }
 }
@@ -0,0 +1,88 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use super::board_manager::BoardSizeInfo;
+use super::parse::piece_type_to_uci_character;
+use super::piece::PieceType;
+
+/// the number of the Fischer random start positions
+pub const POSITION_COUNT: usize = 960;
+
+/// the index of the standard chess start position
+pub const STANDARD_POSITION_INDEX: usize = 518;
+
+/// the places of the knights among the five squares left after the bishops and the queen
+const KNIGHT_PLACES: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// the back row pieces of the start position with the index, using the Scharnagl numbering
+pub fn back_row(index: usize) -> Option<[PieceType; 8]> {
+    if index >= POSITION_COUNT {
+        return None;
+    }
+    let mut back_row: [Option<PieceType>; 8] = [None; 8];
+    let mut index = index;
+
+    // the light squared bishop on b, d, f or h then the dark squared one on a, c, e or g
+    back_row[(index % 4) * 2 + 1] = Some(PieceType::Bishop);
+    index /= 4;
+    back_row[(index % 4) * 2] = Some(PieceType::Bishop);
+    index /= 4;
+
+    let queen_place = index % 6;
+    index /= 6;
+    place_on_empty_square(&mut back_row, queen_place, PieceType::Queen);
+
+    // placing the second knight first keeps the place of the first one correct
+    let (first_knight, second_knight) = KNIGHT_PLACES[index];
+    place_on_empty_square(&mut back_row, second_knight, PieceType::Knight);
+    place_on_empty_square(&mut back_row, first_knight, PieceType::Knight);
+
+    // the king always stands between the two rooks
+    for p_type in [PieceType::Rook, PieceType::King, PieceType::Rook] {
+        place_on_empty_square(&mut back_row, 0, p_type);
+    }
+
+    Some(back_row.map(Option::unwrap))
+}
+
+/// put the piece on the nth empty square of the back row
+fn place_on_empty_square(back_row: &mut [Option<PieceType>; 8], nth: usize, p_type: PieceType) {
+    let square = back_row
+        .iter_mut()
+        .filter(|square| square.is_none())
+        .nth(nth)
+        .unwrap();
+    *square = Some(p_type);
+}
+
+/// the fen string of the start position with the index
+pub fn start_fen(index: usize) -> Option<String> {
+    let back_row: String = back_row(index)?
+        .iter()
+        .map(piece_type_to_uci_character)
+        .collect();
+    let empty_rows = vec!["8"; BoardSizeInfo::default().row_count() - 4].join("/");
+    Some(format!(
+        "{}/pppppppp/{}/PPPPPPPP/{} w KQkq - 0 1",
+        back_row,
+        empty_rows,
+        back_row.to_uppercase()
+    ))
+}
+
+/// pick one of the start positions by random
+pub fn random_index() -> usize {
+    let random_number = RandomState::new().build_hasher().finish();
+    (random_number % POSITION_COUNT as u64) as usize
+}
@@ -0,0 +1,142 @@
+use super::board_manager::{BoardManager, BoardSizeInfo, GameStatus};
+use super::parse::ChessMove;
+use super::piece::{Color, PieceType, Position};
+
+/// score of a mate, bigger than any material difference
+const MATE_SCORE: i32 = 100_000;
+
+/// the material worth of the piece type in centipawns
+pub fn piece_value(p_type: &PieceType) -> i32 {
+    match p_type {
+        PieceType::Pawn => 100,
+        PieceType::Knight => 320,
+        PieceType::Bishop => 330,
+        PieceType::Rook => 500,
+        PieceType::Queen => 900,
+        PieceType::King => 0,
+        PieceType::Archbishop => 825,
+        PieceType::Chancellor => 875,
+        PieceType::Amazon => 1200,
+        PieceType::Camel => 250,
+        PieceType::Zebra => 250,
+        PieceType::Grasshopper => 200,
+    }
+}
+
+/// small bonus for pieces standing near the center of the board
+fn centralization_bonus(p_type: &PieceType, position: &Position, size: &BoardSizeInfo) -> i32 {
+    if *p_type == PieceType::King {
+        return 0;
+    }
+    let center_row = size.last_row() as f32 / 2.0;
+    let center_column = size.last_column() as f32 / 2.0;
+    let distance =
+        (position.x as f32 - center_row).abs() + (position.y as f32 - center_column).abs();
+    (center_row + center_column - distance) as i32 * 2
+}
+
+/// evaluate the board from the point of view of the side to move
+pub fn evaluate(board: &BoardManager) -> i32 {
+    let mut score = 0;
+    let size = board.size();
+    for row in 0..size.row_count() as i8 {
+        for column in 0..size.column_count() as i8 {
+            let position = Position::new(row, column);
+            if let Some(piece) = board.piece_at(&position) {
+                let worth = piece_value(&piece.p_type)
+                    + centralization_bonus(&piece.p_type, &position, &size);
+                if piece.p_color == Color::White {
+                    score += worth;
+                } else {
+                    score -= worth;
+                }
+            }
+        }
+    }
+
+    let pockets = board.pockets();
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        for p_type in pockets.of(color).pieces() {
+            score += sign * piece_value(&p_type);
+        }
+    }
+
+    let score = board.variant().adjust_score(board, score);
+
+    match board.side_to_move() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+/// search the best move for the side to move, looking depth moves ahead
+pub fn best_move(board: &mut BoardManager, depth: u8) -> Option<ChessMove> {
+    search(board, depth).map(|(chess_move, _)| chess_move)
+}
+
+/// the best move and its score for the side to move, looking depth moves ahead
+pub fn search(board: &mut BoardManager, depth: u8) -> Option<(ChessMove, i32)> {
+    let mut best = None;
+    let mut alpha = -MATE_SCORE - 1;
+    let beta = MATE_SCORE + 1;
+
+    for chess_move in board.legal_moves() {
+        board.do_move_regardless(&chess_move);
+        let score = -negamax(board, depth.saturating_sub(1), -beta, -alpha);
+        board.undo_move_regardless();
+
+        if best.is_none() || score > alpha {
+            alpha = score;
+            best = Some(chess_move);
+        }
+    }
+    best.map(|chess_move| (chess_move, alpha))
+}
+
+/// the score is a game won or lost rather than a material difference
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_SCORE
+}
+
+/// negamax search with alpha beta pruning
+fn negamax(board: &mut BoardManager, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+    if let Some(game_status) = board.variant_status() {
+        return status_score(board, &game_status, depth);
+    }
+    if depth == 0 {
+        return evaluate(board);
+    }
+
+    let legal_moves = board.legal_moves();
+    if legal_moves.is_empty() {
+        if let Some(game_status) = board.variant().status_without_moves(board) {
+            return status_score(board, &game_status, depth);
+        }
+        if board.is_check(board.side_to_move()) {
+            // prefer the closer mates
+            return -MATE_SCORE - depth as i32;
+        }
+        return 0;
+    }
+
+    for chess_move in legal_moves {
+        board.do_move_regardless(&chess_move);
+        let score = -negamax(board, depth - 1, -beta, -alpha);
+        board.undo_move_regardless();
+
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// the score of a game the variant ended for the side to move, the closer wins count more
+fn status_score(board: &BoardManager, game_status: &GameStatus, depth: u8) -> i32 {
+    match game_status.winner() {
+        Some(winner) if winner == board.side_to_move() => MATE_SCORE + depth as i32,
+        Some(_) => -MATE_SCORE - depth as i32,
+        None => 0,
+    }
+}
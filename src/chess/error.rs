@@ -0,0 +1,252 @@
+use std::error::Error;
+use std::fmt;
+
+use super::piece::Color;
+
+/// the reason a move was rejected by the board
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum MoveError {
+    OutsideBoard,
+    EmptySource,
+    WrongSide,
+    NotMoving,
+    OwnPieceCapture,
+    IllegalPattern,
+    IllegalPawnMove,
+    PathBlocked,
+    LeavesKingInCheck,
+    BadPromotion,
+    MissingPromotion,
+    CastlingNotAllowed,
+    CastlingBlocked,
+    CastlingThroughCheck,
+    KingCapture,
+    ExplodesOwnKing,
+    DropNotAllowed,
+    NotInPocket,
+    DropOnOccupied,
+    PawnDropOnBackRank,
+    CaptureRequired,
+    GivesCheck,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            MoveError::OutsideBoard => "Illegal Move, Position Is Outside The Board",
+            MoveError::EmptySource => "Illegal Move, Can't Move An Empty Square",
+            MoveError::WrongSide => "Illegal Move, It Is The Other Side's Turn",
+            MoveError::NotMoving => "Illegal Move, The Piece Must Leave Its Square",
+            MoveError::OwnPieceCapture => "Can't Eat The Same Color",
+            MoveError::IllegalPattern => "Piece Can't Move That Way",
+            MoveError::IllegalPawnMove => "Pawn Moves Forward And Eats Only Diagonally",
+            MoveError::PathBlocked => "That Piece Movement Path Is Blocked",
+            MoveError::LeavesKingInCheck => "Can't Make A Move That Danger The King",
+            MoveError::BadPromotion => {
+                "Only Pawn Reaching The Last Row Can Be Promoted, To A Piece The Game Allows"
+            }
+            MoveError::MissingPromotion => "Pawn Reaching The Last Row Must Be Promoted",
+            MoveError::CastlingNotAllowed => {
+                "Castling Not Allowed, The King Or The Rook Already Moved"
+            }
+            MoveError::CastlingBlocked => "Castling Not Allowed, The Path Is Blocked",
+            MoveError::CastlingThroughCheck => {
+                "Castling Not Allowed, The King Can't Pass Through An Attacked Square"
+            }
+            MoveError::KingCapture => "In Atomic Chess The King Can't Capture",
+            MoveError::ExplodesOwnKing => "Can't Make A Capture That Explodes The Own King",
+            MoveError::DropNotAllowed => "Pieces Can Be Dropped Only In Crazyhouse",
+            MoveError::NotInPocket => "That Piece Is Not In The Pocket",
+            MoveError::DropOnOccupied => "Pieces Can Be Dropped Only On Empty Squares",
+            MoveError::PawnDropOnBackRank => "Pawns Can't Be Dropped On The First Or Last Row",
+            MoveError::CaptureRequired => "A Capture Is Possible So It Must Be Made",
+            MoveError::GivesCheck => "In Racing Kings No Move Can Give Check",
+            MoveError::NothingToUndo => "No Move To Undo",
+            MoveError::NothingToRedo => "No Move To Redo",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl Error for MoveError {}
+
+/// the reason a fen string could not be loaded
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum FenError {
+    Empty,
+    WrongRowCount,
+    RowTooLong,
+    RowTooShort,
+    InvalidPiece(char),
+    MissingKing(Color),
+    InvalidSideToMove,
+    InvalidCastlingRights,
+    InvalidEnPassant,
+    InvalidHalfmoveClock,
+    InvalidFullmoveNumber,
+    InvalidCheckCounters,
+    InvalidPocket,
+    UnexpectedPocket,
+    UnexpectedCheckCounters,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::Empty => write!(f, "Fen String Is Empty"),
+            FenError::WrongRowCount => write!(f, "Fen String Must Describe Every Row Of The Board"),
+            FenError::RowTooLong => write!(f, "Fen String Row Is Longer Than The Board"),
+            FenError::RowTooShort => write!(f, "Fen String Row Is Shorter Than The Board"),
+            FenError::InvalidPiece(piece_char) => {
+                write!(
+                    f,
+                    "'{}' Is Not A Valid Character For Chess Fen String",
+                    piece_char
+                )
+            }
+            FenError::MissingKing(color) => write!(f, "{:?} King Not Found", color),
+            FenError::InvalidSideToMove => write!(f, "Fen Side To Move Must Be w Or b"),
+            FenError::InvalidCastlingRights => {
+                write!(
+                    f,
+                    "Fen Castling Rights Must Be - Or Made Of K, Q, k, q \
+                     And The Column Letters Of The Rooks, For Example KQkq Or HAha"
+                )
+            }
+            FenError::InvalidEnPassant => write!(
+                f,
+                "Fen En Passant Square Must Be - Or The Square A Pawn Just Skipped"
+            ),
+            FenError::InvalidHalfmoveClock => write!(f, "Fen Halfmove Clock Must Be A Number"),
+            FenError::InvalidFullmoveNumber => {
+                write!(f, "Fen Fullmove Number Must Be A Number Starting At 1")
+            }
+            FenError::InvalidPocket => {
+                write!(
+                    f,
+                    "Fen Pocket Must Be Piece Letters Between [ And ], For Example [Qn]"
+                )
+            }
+            FenError::InvalidCheckCounters => {
+                write!(
+                    f,
+                    "Fen Check Counters Must Be +N+M With Up To 3 Checks Each"
+                )
+            }
+            FenError::UnexpectedPocket => {
+                write!(f, "Fen Pocket Given For A Variant Without Pockets")
+            }
+            FenError::UnexpectedCheckCounters => {
+                write!(
+                    f,
+                    "Fen Check Counters Given For A Variant That Doesn't Count Checks"
+                )
+            }
+        }
+    }
+}
+
+impl Error for FenError {}
+
+impl From<PieceCharError> for FenError {
+    fn from(error: PieceCharError) -> Self {
+        FenError::InvalidPiece(error.0)
+    }
+}
+
+/// a character that is not the letter of a piece
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct PieceCharError(pub char);
+
+impl fmt::Display for PieceCharError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' Is Not The Letter Of A Piece", self.0)
+    }
+}
+
+impl Error for PieceCharError {}
+
+/// the reason a move or a square could not be parsed from text
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ParseError {
+    InvalidSquare,
+    InvalidMoveLength,
+    InvalidPromotion(char),
+    InvalidDrop(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidSquare => write!(
+                f,
+                "Invalid Algebraic Notation, A Square Is A Column Letter And A Row Number, For Example e4"
+            ),
+            ParseError::InvalidMoveLength => write!(
+                f,
+                "Chess Move Is Two Squares And Optional Promotion, For Example e2e4 Or e7e8q"
+            ),
+            ParseError::InvalidPromotion(piece_char) => write!(
+                f,
+                "'{}' Is Not A Valid Promotion Piece, Use The Lowercase Letter Of Any Piece But The Pawn",
+                piece_char
+            ),
+            ParseError::InvalidDrop(piece_char) => write!(
+                f,
+                "'{}' Is Not A Piece That Can Be Dropped, Use The Letter Of Any Piece But The King",
+                piece_char
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// the reason a pgn game could not be read
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum PgnError {
+    InvalidTag(String),
+    UnclosedComment,
+    UnknownVariant(String),
+    Fen(FenError),
+    /// the move of the color in the full move of the number
+    IllegalMove {
+        move_number: usize,
+        color: Color,
+        san: String,
+    },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::InvalidTag(line) => {
+                write!(f, "Pgn Tag Must Be [Name \"Value\"], Found {}", line)
+            }
+            PgnError::UnclosedComment => write!(f, "Pgn Comment Or Variation Is Never Closed"),
+            PgnError::UnknownVariant(name) => write!(f, "Unknown Pgn Variant '{}'", name),
+            PgnError::Fen(error) => write!(f, "Pgn Fen Tag Is Not Valid, {}", error),
+            PgnError::IllegalMove {
+                move_number,
+                color,
+                san,
+            } => write!(
+                f,
+                "Pgn Move {}{} {} Is Not Legal",
+                move_number,
+                if *color == Color::White { "." } else { "..." },
+                san
+            ),
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+impl From<FenError> for PgnError {
+    fn from(error: FenError) -> Self {
+        PgnError::Fen(error)
+    }
+}
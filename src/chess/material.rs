@@ -0,0 +1,75 @@
+use super::engine::piece_value;
+use super::piece::{Color, PieceType, PIECE_TYPES};
+
+/// the worth of the piece type in the points players count, a pawn is one point
+pub fn piece_points(p_type: PieceType) -> u32 {
+    ((piece_value(&p_type) + 50) / 100) as u32
+}
+
+/// how many pieces of each type a color has
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct SideMaterial([u8; PIECE_TYPES.len()]);
+
+impl SideMaterial {
+    fn index(p_type: PieceType) -> usize {
+        PIECE_TYPES
+            .iter()
+            .position(|piece_type| *piece_type == p_type)
+            .unwrap()
+    }
+
+    /// how many pieces of the type the color has
+    pub fn count(&self, p_type: PieceType) -> u8 {
+        self.0[SideMaterial::index(p_type)]
+    }
+
+    /// the points of all the pieces of the color
+    pub fn points(&self) -> u32 {
+        PIECE_TYPES
+            .iter()
+            .map(|p_type| self.count(*p_type) as u32 * piece_points(*p_type))
+            .sum()
+    }
+
+    pub(crate) fn add(&mut self, p_type: PieceType) {
+        self.0[SideMaterial::index(p_type)] += 1;
+    }
+}
+
+/// the pieces both colors have, on the board and in the pockets
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct Material {
+    pub white: SideMaterial,
+    pub black: SideMaterial,
+}
+
+impl Material {
+    /// the material of the color
+    pub fn of(&self, color: Color) -> &SideMaterial {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    pub(crate) fn of_mut(&mut self, color: Color) -> &mut SideMaterial {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+
+    /// the points of white minus the points of black
+    pub fn balance(&self) -> i32 {
+        self.white.points() as i32 - self.black.points() as i32
+    }
+
+    /// the color that is up material and by how many points, None when even
+    pub fn advantage(&self) -> Option<(Color, u32)> {
+        match self.balance() {
+            0 => None,
+            balance if balance > 0 => Some((Color::White, balance as u32)),
+            balance => Some((Color::Black, balance.unsigned_abs())),
+        }
+    }
+}
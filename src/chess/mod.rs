@@ -1,4 +1,13 @@
 pub mod board_manager;
+pub mod chess960;
+pub mod engine;
+pub mod error;
+pub mod material;
 pub mod parse;
+pub mod perft;
+pub mod pgn;
 pub mod piece;
 pub mod piece_movement;
+pub mod renderer;
+pub mod svg;
+pub mod variant;
@@ -1,30 +1,27 @@
-use std::error::Error;
+use std::fmt;
 
 use crate::chess::piece::{Piece, PieceType};
 
-use super::board_manager::BoardSizeInfo;
+use super::board_manager::{CastlingRights, MAX_BOARD_SIZE};
+use super::error::ParseError;
 use super::piece::Position;
 
 //todo! read input string and convert to ChessMove and handle
 // castling + pawn promption
 
 // NOTE: CAPLOCK + K is like mouse press
-type MyResult<T> = Result<T, Box<dyn Error>>;
-
-pub struct AlgebraicNotation {
-    square_source: u8,
-    square_dest: u8,
-    promotion: Option<u8>,
-}
 
 /// chess move is only the action in the turn
 /// for example Pawn prompted: e7e8q
 /// castling is just like movingthe king to the rook
+/// and a crazyhouse drop is the piece from the pocket landing on its destination: N@f3
 #[derive(Debug, Copy, Clone)]
 pub struct ChessMove {
     pub piece_source: Position,
     pub piece_dest: Position,
     pub prompted: Option<PieceType>,
+    /// the piece dropped from the pocket, the source is then the destination too
+    pub dropped: Option<PieceType>,
 }
 
 impl ChessMove {
@@ -33,7 +30,41 @@ impl ChessMove {
             piece_source: ps,
             piece_dest: pd,
             prompted: pr,
+            dropped: None,
+        }
+    }
+
+    /// drop the piece from the pocket on the destination
+    pub fn new_drop(p_type: PieceType, pd: Position) -> Self {
+        ChessMove {
+            piece_source: pd,
+            piece_dest: pd,
+            prompted: None,
+            dropped: Some(p_type),
+        }
+    }
+}
+
+impl fmt::Display for ChessMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(p_type) = self.dropped {
+            return write!(
+                f,
+                "{}@{}",
+                piece_type_to_uci_character(&p_type).to_ascii_uppercase(),
+                position_to_algebraic_notation(&self.piece_dest)
+            );
+        }
+        write!(
+            f,
+            "{}{}",
+            position_to_algebraic_notation(&self.piece_source),
+            position_to_algebraic_notation(&self.piece_dest)
+        )?;
+        if let Some(p_type) = self.prompted {
+            write!(f, "{}", piece_type_to_uci_character(&p_type))?;
         }
+        Ok(())
     }
 }
 
@@ -44,27 +75,60 @@ impl ChessMove {
 pub struct ChessTurn {
     pub chess_move: ChessMove,
     pub piece_eaten: Option<Piece>,
+    pub castling: bool,
+    pub en_passant: bool,
+    /// the castling rights before the turn
+    pub castling_rights: CastlingRights,
+    /// the en passant square before the turn
+    pub en_passant_square: Option<Position>,
+    /// the halfmove clock before the turn
+    pub halfmove_clock: usize,
+    /// the turn was counted as a check given, in three-check
+    pub gave_check: bool,
+    /// the pieces removed by the explosion of an atomic capture, the capturing piece included
+    pub exploded: [Option<(Position, Piece)>; 9],
+    /// the eaten piece was a promoted pawn, in crazyhouse it goes to the pocket as a pawn
+    pub eaten_promoted: bool,
 }
 
+impl ChessTurn {
+    /// the square of the eaten piece, differs from the move destination on en passant
+    pub fn eaten_at(&self) -> Position {
+        if self.en_passant {
+            return Position::new(self.chess_move.piece_source.x, self.chess_move.piece_dest.y);
+        }
+        self.chess_move.piece_dest
+    }
+}
+
+/// the letters of the standard pieces and the fairy pieces, h and e are the seirawan
+/// names of the archbishop and the chancellor
 pub fn is_valid_uci_piece_character(piece_char: &u8) -> bool {
-    ['r', 'k', 'b', 'q', 'n', 'p'].contains(&(*piece_char as char))
+    piece_char.is_ascii_lowercase() && PieceType::try_from(*piece_char as char).is_ok()
+}
+
+/// the lowercase letter of the piece type as used by uci promotions
+pub fn piece_type_to_uci_character(p_type: &PieceType) -> char {
+    p_type.to_fen_char()
 }
 
 pub fn is_valid_promotion(piece_char: &u8) -> bool {
     is_valid_uci_piece_character(piece_char) && (*piece_char as char) != 'k'
 }
 
+/// the columns go from a up to the letter of the widest board, the board checks its own width
 pub fn is_valid_algebraic_notation_column(column: &char) -> bool {
-    ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'].contains(column)
+    column.is_ascii_lowercase() && (*column as u8 - b'a') < MAX_BOARD_SIZE as u8
 }
 
+/// a single digit row, rows of two digits are parsed by parse_square
 pub fn is_valid_algebraic_notation_row(row: &char) -> bool {
     let column = row.to_digit(10);
     if column.is_none() {
         return false;
     }
     let column = column.unwrap();
-    column <= (BoardSizeInfo::row_count() as u32)
+    column >= 1 && column <= (MAX_BOARD_SIZE as u32)
 }
 
 /// check if algebraic notation is valid
@@ -73,18 +137,91 @@ pub fn is_valid_algebraic_notation(column: &u8, row: &u8) -> bool {
         && is_valid_algebraic_notation_row(&(*row as char))
 }
 
-/// need to parse string to a move,
-/// for example castling: e1g1 and promotion e7e8q
-/// todo! array of 5 letters, and to add support for
-/// piece pawn promotion and castling
+// need to parse string to a move,
+// for example castling: e1g1 and promotion e7e8q
+// todo! array of 5 letters, and to add support for
+// piece pawn promotion and castling
 
 /// parse algebraic notation to Position on board note the subtraction of 1
 /// for board array indexing that starts with 0
-pub fn parse_algebraic_notation(col: &u8, row: &u8) -> MyResult<Position> {
+pub fn parse_algebraic_notation(col: &u8, row: &u8) -> Result<Position, ParseError> {
     if is_valid_algebraic_notation(col, row) {
         let column = col - b'a';
         let row = (*row as char).to_digit(10).unwrap() - 1;
         return Ok(Position::new(row as i8, column as i8));
     }
-    Err("Invalid algebraic notation for piece location")?
+    Err(ParseError::InvalidSquare)
+}
+
+/// format Position on board as algebraic notation, for example e4
+pub fn position_to_algebraic_notation(position: &Position) -> String {
+    let column = (b'a' + position.y as u8) as char;
+    format!("{}{}", column, position.x + 1)
+}
+
+/// parse a single square written in algebraic notation, for example e4 or a10
+pub fn parse_square(square: &str) -> Result<Position, ParseError> {
+    match split_square(square)? {
+        (position, "") => Ok(position),
+        _ => Err(ParseError::InvalidSquare),
+    }
+}
+
+/// parse the square at the start of the text, a column letter and a row number of one or two digits,
+/// and return the rest of the text after it
+fn split_square(text: &str) -> Result<(Position, &str), ParseError> {
+    let column = text.bytes().next().ok_or(ParseError::InvalidSquare)?;
+    // a column that isn't ascii would put byte 1 inside a char
+    let after_column = text.get(1..).ok_or(ParseError::InvalidSquare)?;
+    let row_length = after_column.bytes().take_while(u8::is_ascii_digit).count();
+    let (row, rest) = after_column.split_at(row_length);
+    let row: usize = row.parse().map_err(|_| ParseError::InvalidSquare)?;
+    if !is_valid_algebraic_notation_column(&(column as char))
+        || !(1..=MAX_BOARD_SIZE).contains(&row)
+    {
+        return Err(ParseError::InvalidSquare);
+    }
+    Ok((Position::new(row as i8 - 1, (column - b'a') as i8), rest))
+}
+
+/// parse a uci like move, two squares and optional promotion, for example e7e8q,
+/// or a crazyhouse drop, a piece letter and a square, for example N@f3
+pub fn parse_chess_move(chess_move: &str) -> Result<ChessMove, ParseError> {
+    if !chess_move.is_ascii() {
+        return Err(ParseError::InvalidSquare);
+    }
+    if let Some((piece_char, square)) = chess_move.split_once('@') {
+        let piece_char = match piece_char.as_bytes() {
+            [piece_char] => piece_char.to_ascii_lowercase(),
+            _ => return Err(ParseError::InvalidMoveLength),
+        };
+        let p_type = match PieceType::try_from(piece_char as char) {
+            Ok(p_type) if p_type != PieceType::King => p_type,
+            _ => return Err(ParseError::InvalidDrop(piece_char as char)),
+        };
+        let dest_square = parse_square(square)?;
+        return Ok(ChessMove::new_drop(p_type, dest_square));
+    }
+
+    // two squares of two or three characters and an optional promotion letter
+    if chess_move.len() < 4 || chess_move.len() > 7 {
+        return Err(ParseError::InvalidMoveLength);
+    }
+
+    let (source_square, rest) = split_square(chess_move)?;
+    let (dest_square, rest) = split_square(rest)?;
+
+    let promotion_type = match rest.as_bytes() {
+        [] => None,
+        // promoting to a king is parsed too, only antichess allows it
+        [promotion_char] => match PieceType::try_from(*promotion_char as char) {
+            Ok(p_type) if promotion_char.is_ascii_lowercase() && p_type != PieceType::Pawn => {
+                Some(p_type)
+            }
+            _ => return Err(ParseError::InvalidPromotion(*promotion_char as char)),
+        },
+        _ => return Err(ParseError::InvalidMoveLength),
+    };
+
+    Ok(ChessMove::new(source_square, dest_square, promotion_type))
 }
@@ -0,0 +1,34 @@
+use super::board_manager::BoardManager;
+use super::parse::ChessMove;
+
+/// count the positions reached after depth moves, the usual check of the move generator
+pub fn perft(board: &mut BoardManager, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let legal_moves = board.legal_moves();
+    if depth == 1 {
+        return legal_moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for chess_move in legal_moves {
+        board.do_move_regardless(&chess_move);
+        nodes += perft(board, depth - 1);
+        board.undo_move_regardless();
+    }
+    nodes
+}
+
+/// the positions counted after each legal move, to find the move a count goes wrong on
+pub fn divide(board: &mut BoardManager, depth: u8) -> Vec<(ChessMove, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    let mut counts = Vec::new();
+    for chess_move in board.legal_moves() {
+        board.do_move_regardless(&chess_move);
+        counts.push((chess_move, perft(board, depth - 1)));
+        board.undo_move_regardless();
+    }
+    counts
+}
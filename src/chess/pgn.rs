@@ -0,0 +1,385 @@
+use std::rc::Rc;
+
+use super::board_manager::{BoardManager, GameStatus};
+use super::error::PgnError;
+use super::parse::{position_to_algebraic_notation, ChessMove};
+use super::piece::{Color, PieceType};
+use super::variant::{Variant, VariantKind};
+
+/// the longest line of the move text
+const LINE_WIDTH: usize = 80;
+
+/// the tags every pgn game starts with, in their order
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// a game read from pgn, the moves are in standard algebraic notation
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PgnGame {
+    /// the tags in the order they were written
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+    /// the result that ends the move text, 1-0, 0-1, 1/2-1/2 or *
+    pub result: String,
+}
+
+impl PgnGame {
+    /// the value of the tag if the game has it
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// the board of the game with every move played on it, so the moves can be taken back
+    pub fn board(&self) -> Result<BoardManager, PgnError> {
+        let (variant, chess960) = match self.tag("Variant") {
+            None => (VariantKind::Standard, false),
+            Some(name) => parse_variant_tag(name)?,
+        };
+        let mut board = match self.tag("FEN") {
+            Some(fen_string) => BoardManager::try_from_variant_fen(fen_string, variant)?,
+            None => BoardManager::new_variant(variant),
+        };
+        if chess960 {
+            board.set_chess960(true);
+        }
+
+        for san in &self.moves {
+            let illegal_move = PgnError::IllegalMove {
+                move_number: board.fullmove_number(),
+                color: board.side_to_move(),
+                san: san.clone(),
+            };
+            let chess_move = parse_san(&mut board, san).ok_or(illegal_move.clone())?;
+            board.handle_move(&chess_move).map_err(|_| illegal_move)?;
+        }
+        Ok(board)
+    }
+}
+
+/// the variant and whether it is Fischer random by the name of the Variant tag
+fn parse_variant_tag(name: &str) -> Result<(VariantKind, bool), PgnError> {
+    let compact: String = name
+        .to_lowercase()
+        .chars()
+        .filter(|letter| letter.is_ascii_alphanumeric())
+        .collect();
+    match compact.as_str() {
+        "chess960" | "fischerandom" | "fischerrandom" => Ok((VariantKind::Standard, true)),
+        _ => VariantKind::from_name(&compact)
+            .map(|variant| (variant, false))
+            .ok_or_else(|| PgnError::UnknownVariant(name.to_string())),
+    }
+}
+
+/// the move in standard algebraic notation, without the check or mate sign,
+/// the board is the position before the move
+fn san_body(board: &BoardManager, chess_move: &ChessMove, legal_moves: &[ChessMove]) -> String {
+    let dest = position_to_algebraic_notation(&chess_move.piece_dest);
+    if let Some(p_type) = chess_move.dropped {
+        return format!("{}@{}", p_type.to_san_letter().unwrap_or('P'), dest);
+    }
+
+    let src = chess_move.piece_source;
+    let piece = match board.piece_at(&src) {
+        Some(piece) => piece,
+        None => return chess_move.to_string(),
+    };
+    let target = board.piece_at(&chess_move.piece_dest);
+    let castling = piece.p_type == PieceType::King
+        && (target.is_some_and(|target| {
+            target.p_color == piece.p_color && target.p_type == PieceType::Rook
+        }) || (chess_move.piece_dest.y - src.y).abs() > 1);
+    if castling {
+        return if chess_move.piece_dest.y > src.y {
+            "O-O"
+        } else {
+            "O-O-O"
+        }
+        .to_string();
+    }
+
+    let source_square = position_to_algebraic_notation(&src);
+    let mut san = String::new();
+    match piece.p_type.to_san_letter() {
+        None => {
+            if src.y != chess_move.piece_dest.y {
+                san.push_str(&source_square[..1]);
+                san.push('x');
+            }
+        }
+        Some(letter) => {
+            san.push(letter);
+            // the other pieces of the type that can go to the same square
+            let rivals: Vec<&ChessMove> = legal_moves
+                .iter()
+                .filter(|other| {
+                    other.dropped.is_none()
+                        && other.piece_source != src
+                        && other.piece_dest == chess_move.piece_dest
+                        && board.piece_at(&other.piece_source) == Some(piece)
+                })
+                .collect();
+            if !rivals.is_empty() {
+                if rivals.iter().all(|other| other.piece_source.y != src.y) {
+                    san.push_str(&source_square[..1]);
+                } else if rivals.iter().all(|other| other.piece_source.x != src.x) {
+                    san.push_str(&source_square[1..]);
+                } else {
+                    san.push_str(&source_square);
+                }
+            }
+            if target.is_some() {
+                san.push('x');
+            }
+        }
+    }
+    san.push_str(&dest);
+    if let Some(p_type) = chess_move.prompted {
+        san.push('=');
+        san.push(p_type.to_san_letter().unwrap_or('P'));
+    }
+    san
+}
+
+/// the move in standard algebraic notation with + after a check and # after a mate,
+/// the board is the position before the move
+pub fn move_to_san(board: &mut BoardManager, chess_move: &ChessMove) -> String {
+    let legal_moves = board.legal_moves();
+    let mut san = san_body(board, chess_move, &legal_moves);
+
+    let mut after = board.clone();
+    if after.handle_move(chess_move).is_ok() {
+        let side_to_move = after.side_to_move();
+        if matches!(after.game_status(), GameStatus::Checkmate { .. }) {
+            san.push('#');
+        } else if after.has_king(side_to_move) && after.is_check(side_to_move) {
+            san.push('+');
+        }
+    }
+    san
+}
+
+/// find the legal move written in standard algebraic notation, the check signs and
+/// annotations are ignored and uci moves are accepted too
+pub fn parse_san(board: &mut BoardManager, san: &str) -> Option<ChessMove> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+    let san = match san {
+        "0-0" => "O-O".to_string(),
+        "0-0-0" => "O-O-O".to_string(),
+        san => san.to_string(),
+    };
+    // a pawn drop can be written without the pawn letter
+    let san = if san.starts_with('@') {
+        format!("P{}", san)
+    } else {
+        san
+    };
+    let legal_moves = board.legal_moves();
+    legal_moves
+        .iter()
+        .find(|chess_move| {
+            let body = san_body(board, chess_move, &legal_moves);
+            // the promotion can be written without the equal sign, as in e8Q
+            body == san
+                || body.replace('=', "") == san
+                || board.move_to_uci(chess_move) == san
+                || chess_move.to_string() == san
+        })
+        .copied()
+}
+
+/// write the game played on the board as pgn, the given tags replace the seven tag roster
+/// values or follow them, the variant and the start position are written as tags too
+pub fn write_pgn(board: &BoardManager, tags: &[(&str, String)], result: &str) -> String {
+    let mut start = board.clone();
+    while start.undo().is_ok() {}
+
+    let mut text = String::new();
+    for (name, default) in SEVEN_TAG_ROSTER {
+        let value = match tags.iter().find(|(tag_name, _)| *tag_name == name) {
+            Some((_, value)) => value.as_str(),
+            None if name == "Result" => result,
+            None => default,
+        };
+        text.push_str(&tag_line(name, value));
+    }
+
+    let variant = Rc::clone(board.variant());
+    if variant.name() != VariantKind::Standard.name() {
+        text.push_str(&tag_line("Variant", variant.title()));
+    } else if board.is_chess960() {
+        text.push_str(&tag_line("Variant", "Chess960"));
+    }
+    // the fen of a new board, with the check counters, pockets and castling rights the
+    // variant adds to its start position
+    let start_fen = start.to_fen();
+    if start_fen != BoardManager::new_variant(Rc::clone(&variant)).to_fen() {
+        text.push_str(&tag_line("SetUp", "1"));
+        text.push_str(&tag_line("FEN", &start_fen));
+    }
+    for (name, value) in tags {
+        if !SEVEN_TAG_ROSTER
+            .iter()
+            .any(|(roster_name, _)| roster_name == name)
+        {
+            text.push_str(&tag_line(name, value));
+        }
+    }
+    text.push('\n');
+
+    let mut tokens = Vec::new();
+    let mut replay = start;
+    for (ply, turn) in board.history().iter().enumerate() {
+        let move_number = replay.fullmove_number();
+        match replay.side_to_move() {
+            Color::White => tokens.push(format!("{}.", move_number)),
+            Color::Black if ply == 0 => tokens.push(format!("{}...", move_number)),
+            Color::Black => {}
+        }
+        tokens.push(move_to_san(&mut replay, &turn.chess_move));
+        if replay.handle_move(&turn.chess_move).is_err() {
+            break;
+        }
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    text.push_str(&line);
+    text.push('\n');
+    text
+}
+
+fn tag_line(name: &str, value: &str) -> String {
+    format!(
+        "[{} \"{}\"]\n",
+        name,
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// read the first game of the pgn text, comments, variations and annotation glyphs
+/// are skipped
+pub fn parse_pgn(text: &str) -> Result<PgnGame, PgnError> {
+    let mut game = PgnGame::default();
+    let mut lines = text.lines().map(str::trim).peekable();
+
+    while let Some(line) = lines.peek() {
+        if line.is_empty() {
+            lines.next();
+            continue;
+        }
+        if !line.starts_with('[') {
+            break;
+        }
+        game.tags.push(parse_tag(line)?);
+        lines.next();
+    }
+
+    let move_text: Vec<&str> = lines.collect();
+    let move_text = move_text.join("\n");
+    let mut chars = move_text.chars().peekable();
+    let mut token = String::new();
+    let mut tokens = Vec::new();
+    while let Some(letter) = chars.next() {
+        match letter {
+            '{' => {
+                if !chars.by_ref().any(|letter| letter == '}') {
+                    return Err(PgnError::UnclosedComment);
+                }
+            }
+            ';' => while chars.next_if(|letter| *letter != '\n').is_some() {},
+            '(' => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some('{') => {
+                            if !chars.by_ref().any(|letter| letter == '}') {
+                                return Err(PgnError::UnclosedComment);
+                            }
+                        }
+                        Some(_) => {}
+                        None => return Err(PgnError::UnclosedComment),
+                    }
+                }
+            }
+            letter if letter.is_whitespace() => tokens.push(std::mem::take(&mut token)),
+            letter => token.push(letter),
+        }
+    }
+    tokens.push(token);
+
+    for token in tokens {
+        if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+            game.result = token;
+            continue;
+        }
+        let token = strip_move_number(&token);
+        if !token.is_empty() && !token.starts_with('$') {
+            game.moves.push(token.to_string());
+        }
+    }
+    if game.result.is_empty() {
+        game.result = game.tag("Result").unwrap_or("*").to_string();
+    }
+    Ok(game)
+}
+
+/// the token without its move number, which can stick to the move as in 1.e4 or 3...Nf6,
+/// the digits of the number are followed by at least one dot so 0-0 is kept
+fn strip_move_number(token: &str) -> &str {
+    let digits = token.bytes().take_while(u8::is_ascii_digit).count();
+    let after_digits = &token[digits..];
+    match after_digits.trim_start_matches('.') {
+        rest if digits > 0 && rest.len() < after_digits.len() => rest,
+        _ => token,
+    }
+}
+
+/// parse a tag line like [Event "Club Championship"]
+fn parse_tag(line: &str) -> Result<(String, String), PgnError> {
+    let invalid_tag = || PgnError::InvalidTag(line.to_string());
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .ok_or_else(invalid_tag)?;
+    let (name, value) = inner
+        .split_once(char::is_whitespace)
+        .ok_or_else(invalid_tag)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(invalid_tag)?;
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(letter) = chars.next() {
+        match letter {
+            '\\' => unescaped.extend(chars.next()),
+            letter => unescaped.push(letter),
+        }
+    }
+    Ok((name.to_string(), unescaped))
+}
@@ -1,7 +1,8 @@
-use core::panic;
-use std::convert::From;
+use std::convert::TryFrom;
 use std::fmt;
 
+use super::error::PieceCharError;
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct Piece {
     pub p_type: PieceType,
@@ -12,6 +13,14 @@ impl Piece {
     pub fn new(p_type: PieceType, p_color: Color) -> Self {
         Piece { p_type, p_color }
     }
+
+    /// the fen letter of the piece, uppercase for white and lowercase for black
+    pub fn to_fen_char(&self) -> char {
+        match self.p_color {
+            Color::White => self.p_type.to_fen_char().to_ascii_uppercase(),
+            Color::Black => self.p_type.to_fen_char(),
+        }
+    }
 }
 
 impl fmt::Display for Piece {
@@ -26,12 +35,16 @@ pub enum Color {
     White,
 }
 
-impl From<char> for Color {
-    fn from(item: char) -> Self {
+/// the color of a piece letter, uppercase is white and lowercase is black
+impl TryFrom<char> for Color {
+    type Error = PieceCharError;
+
+    fn try_from(item: char) -> Result<Self, Self::Error> {
+        PieceType::try_from(item)?;
         if item.is_lowercase() {
-            Color::Black
+            Ok(Color::Black)
         } else {
-            Color::White
+            Ok(Color::White)
         }
     }
 }
@@ -44,19 +57,120 @@ pub enum PieceType {
     Rook,
     Queen,
     King,
+    /// moves as bishop and knight
+    Archbishop,
+    /// moves as rook and knight
+    Chancellor,
+    /// moves as queen and knight
+    Amazon,
+    /// leaps 3 squares one way and 1 square the other
+    Camel,
+    /// leaps 3 squares one way and 2 squares the other
+    Zebra,
+    /// moves along queen lines by hopping over the first piece, landing right behind it
+    Grasshopper,
+}
+
+impl PieceType {
+    /// the betza descriptor of the piece movement, None for the pawn as its moves depend
+    /// on the color and on captures and are handled by the board
+    pub fn betza(&self) -> Option<&'static str> {
+        match self {
+            PieceType::Pawn => None,
+            PieceType::Knight => Some("N"),
+            PieceType::Bishop => Some("B"),
+            PieceType::Rook => Some("R"),
+            PieceType::Queen => Some("Q"),
+            PieceType::King => Some("K"),
+            PieceType::Archbishop => Some("BN"),
+            PieceType::Chancellor => Some("RN"),
+            PieceType::Amazon => Some("QN"),
+            PieceType::Camel => Some("C"),
+            PieceType::Zebra => Some("Z"),
+            PieceType::Grasshopper => Some("gQ"),
+        }
+    }
+
+    /// check if the piece is not one of the six pieces of standard chess
+    pub fn is_fairy(&self) -> bool {
+        FAIRY_PIECES.contains(self)
+    }
+
+    /// the lowercase fen letter of the piece type
+    pub fn to_fen_char(&self) -> char {
+        match self {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+            PieceType::Archbishop => 'a',
+            PieceType::Chancellor => 'c',
+            PieceType::Amazon => 'm',
+            PieceType::Camel => 'l',
+            PieceType::Zebra => 'z',
+            PieceType::Grasshopper => 'g',
+        }
+    }
+
+    /// the uppercase letter of the piece in standard algebraic notation, pawns have none
+    pub fn to_san_letter(&self) -> Option<char> {
+        match self {
+            PieceType::Pawn => None,
+            _ => Some(self.to_fen_char().to_ascii_uppercase()),
+        }
+    }
 }
 
-impl From<char> for PieceType {
-    fn from(item: char) -> Self {
-        match item {
+/// every piece type, in the order of the enum
+pub const PIECE_TYPES: [PieceType; 12] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+    PieceType::Archbishop,
+    PieceType::Chancellor,
+    PieceType::Amazon,
+    PieceType::Camel,
+    PieceType::Zebra,
+    PieceType::Grasshopper,
+];
+
+/// the pieces that are not part of standard chess
+pub const FAIRY_PIECES: [PieceType; 6] = [
+    PieceType::Archbishop,
+    PieceType::Chancellor,
+    PieceType::Amazon,
+    PieceType::Camel,
+    PieceType::Zebra,
+    PieceType::Grasshopper,
+];
+
+/// the piece type of a fen letter of either case
+impl TryFrom<char> for PieceType {
+    type Error = PieceCharError;
+
+    fn try_from(item: char) -> Result<Self, Self::Error> {
+        let p_type = match item {
             'p' | 'P' => PieceType::Pawn,
             'k' | 'K' => PieceType::King,
             'n' | 'N' => PieceType::Knight,
             'r' | 'R' => PieceType::Rook,
             'q' | 'Q' => PieceType::Queen,
             'b' | 'B' => PieceType::Bishop,
-            _ => panic!("not a valid character for chess fen string"),
-        }
+            // seirawan chess calls them hawk and elephant
+            'a' | 'A' | 'h' | 'H' => PieceType::Archbishop,
+            'c' | 'C' | 'e' | 'E' => PieceType::Chancellor,
+            'm' | 'M' => PieceType::Amazon,
+            'l' | 'L' => PieceType::Camel,
+            'z' | 'Z' => PieceType::Zebra,
+            'g' | 'G' => PieceType::Grasshopper,
+            _ => return Err(PieceCharError(item)),
+        };
+        Ok(p_type)
     }
 }
 
@@ -72,11 +186,12 @@ impl Position {
     }
 }
 
-impl From<char> for Piece {
-    fn from(item: char) -> Self {
-        let color = item.into();
-        let piece_type = item.into();
-        Piece::new(piece_type, color)
+/// the piece of a fen letter, the case tells its color
+impl TryFrom<char> for Piece {
+    type Error = PieceCharError;
+
+    fn try_from(item: char) -> Result<Self, Self::Error> {
+        Ok(Piece::new(item.try_into()?, item.try_into()?))
     }
 }
 
@@ -129,5 +244,34 @@ pub fn piece_to_char(p_type: &PieceType, p_color: &Color) -> char {
                 '♖'
             }
         }
+
+        PieceType::Archbishop => {
+            if p_color == &Color::Black {
+                '\u{1FA55}'
+            } else {
+                '\u{1FA52}'
+            }
+        }
+
+        PieceType::Chancellor => {
+            if p_color == &Color::Black {
+                '\u{1FA54}'
+            } else {
+                '\u{1FA51}'
+            }
+        }
+
+        PieceType::Amazon => {
+            if p_color == &Color::Black {
+                '\u{1FA53}'
+            } else {
+                '\u{1FA50}'
+            }
+        }
+
+        // unicode has no symbols for the leapers and the grasshopper, their fen letter is used
+        PieceType::Camel | PieceType::Zebra | PieceType::Grasshopper => {
+            Piece::new(*p_type, *p_color).to_fen_char()
+        }
     }
 }
@@ -1,4 +1,6 @@
-use super::piece::{PieceType, Position};
+use std::sync::OnceLock;
+
+use super::piece::{PieceType, Position, PIECE_TYPES};
 
 /// TODO: after finding the piece velocity direction return it converted to direction enum
 /// then travers to there and check if its not blocked by other pieces or so
@@ -40,11 +42,136 @@ pub fn is_valid_move(piece_type: &PieceType, src: &Position, dest: &Position) ->
         Bishop => is_valid_bishop_move(src, dest),
         Queen => is_valid_queen_move(src, dest),
         Rook => is_valid_rook_move(src, dest),
-        Pawn => is_valid_pawn_move(src, dest),
+        // the start rows depend on the board size, the board checks them
+        Pawn => is_valid_pawn_step(src, dest),
         King => is_valid_king_move(src, dest),
+        Archbishop | Chancellor | Amazon | Camel | Zebra | Grasshopper => {
+            find_atom(piece_type, src, dest).is_some()
+        }
     }
 }
 
+/// how a piece gets to the squares of a movement atom
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Reach {
+    /// jumps straight to the square, whatever stands on the way
+    Leap,
+    /// repeats the step as long as the squares on the way are empty
+    Ride,
+    /// goes along the line and hops over the first piece, landing right behind it
+    Hop,
+}
+
+/// a movement of a betza descriptor, the step applies in all 8 directions
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Atom {
+    pub step: (i8, i8),
+    pub reach: Reach,
+}
+
+impl Atom {
+    /// the step direction and the number of steps from src to dest, if the atom gets there
+    pub fn velocity_to(&self, src: &Position, dest: &Position) -> Option<Velocity> {
+        let (dx, dy) = (dest.x - src.x, dest.y - src.y);
+        let (a, b) = self.step;
+        for (x, y) in [(a, b), (b, a)] {
+            for (sign_x, sign_y) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                let (vx, vy) = (x * sign_x, y * sign_y);
+                let scalar = if vx != 0 { dx / vx } else { dy / vy };
+                if scalar >= 1
+                    && vx * scalar == dx
+                    && vy * scalar == dy
+                    && (scalar == 1 || self.reach != Reach::Leap)
+                {
+                    return Some(Velocity {
+                        x: vx,
+                        y: vy,
+                        scalar,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// parse a betza descriptor to its atoms, W F D N A C Z are the leaps of (1, 0), (1, 1),
+/// (2, 0), (2, 1), (2, 2), (3, 1) and (3, 2), a doubled letter rides, B R Q K are short
+/// for FF, WW, WWFF and WF, and g makes the atom hop like the grasshopper.
+/// the direction and move or capture only modifiers like f, m and c are not supported,
+/// the letter that can't be parsed is the error
+pub fn parse_betza(descriptor: &str) -> Result<Vec<Atom>, char> {
+    let mut atoms = Vec::new();
+    let mut letters = descriptor.chars().peekable();
+    while let Some(letter) = letters.next() {
+        let hop = letter == 'g';
+        let letter = if hop {
+            letters.next().ok_or('g')?
+        } else {
+            letter
+        };
+        let (steps, mut reach): (&[(i8, i8)], Reach) = match letter {
+            'W' => (&[(1, 0)], Reach::Leap),
+            'F' => (&[(1, 1)], Reach::Leap),
+            'D' => (&[(2, 0)], Reach::Leap),
+            'N' => (&[(2, 1)], Reach::Leap),
+            'A' => (&[(2, 2)], Reach::Leap),
+            'C' => (&[(3, 1)], Reach::Leap),
+            'Z' => (&[(3, 2)], Reach::Leap),
+            'B' => (&[(1, 1)], Reach::Ride),
+            'R' => (&[(1, 0)], Reach::Ride),
+            'Q' => (&[(1, 0), (1, 1)], Reach::Ride),
+            'K' => (&[(1, 0), (1, 1)], Reach::Leap),
+            other => return Err(other),
+        };
+        if steps.len() == 1 && reach == Reach::Leap && letters.peek() == Some(&letter) {
+            letters.next();
+            reach = Reach::Ride;
+        }
+        if hop {
+            reach = Reach::Hop;
+        }
+        atoms.extend(steps.iter().map(|step| Atom { step: *step, reach }));
+    }
+    Ok(atoms)
+}
+
+/// the movement atoms of the piece parsed once from its betza descriptor,
+/// the pawn has none as the board handles its moves
+pub fn piece_atoms(piece_type: &PieceType) -> &'static [Atom] {
+    static ATOMS: OnceLock<Vec<(PieceType, Vec<Atom>)>> = OnceLock::new();
+    let atoms = ATOMS.get_or_init(|| {
+        PIECE_TYPES
+            .iter()
+            .map(|p_type| {
+                let atoms = match p_type.betza() {
+                    Some(descriptor) => {
+                        parse_betza(descriptor).expect("piece betza descriptors are valid")
+                    }
+                    None => Vec::new(),
+                };
+                (*p_type, atoms)
+            })
+            .collect()
+    });
+    atoms
+        .iter()
+        .find(|(p_type, _)| p_type == piece_type)
+        .map_or(&[], |(_, atoms)| atoms.as_slice())
+}
+
+/// the first atom of the piece that gets from src to dest, with the velocity it takes
+pub fn find_atom(
+    piece_type: &PieceType,
+    src: &Position,
+    dest: &Position,
+) -> Option<(Atom, Velocity)> {
+    piece_atoms(piece_type).iter().find_map(|atom| {
+        atom.velocity_to(src, dest)
+            .map(|velocity| (*atom, velocity))
+    })
+}
+
 /// check if knight move is valid, using distance from it source to dest for
 /// all of the valid moves that have the constancy
 pub fn is_valid_knight_move(src: &Position, dest: &Position) -> bool {
@@ -52,8 +179,15 @@ pub fn is_valid_knight_move(src: &Position, dest: &Position) -> bool {
 }
 
 /// check if pawn move is valid, using the fact that pawn can move
-/// diagonally 1 square to eat, 1 square vertically or 2 if moved the first time
+/// diagonally 1 square to eat, 1 square vertically or 2 if moved the first time,
+/// the start rows are of the standard board, other sizes are checked by the board
 pub fn is_valid_pawn_move(src: &Position, dest: &Position) -> bool {
+    is_valid_pawn_step(src, dest) && ((src.x - dest.x).abs() < 2 || [1, 6].contains(&src.x))
+}
+
+/// check the shape of the pawn move without its start row, a step of 2 squares
+/// must remain in the same column
+pub fn is_valid_pawn_step(src: &Position, dest: &Position) -> bool {
     let v = Velocity::new(src, dest);
     let vx_abs = v.x.abs();
     let vy_abs = v.y.abs();
@@ -69,9 +203,8 @@ pub fn is_valid_pawn_move(src: &Position, dest: &Position) -> bool {
     }
 
     if scalar_abs == 2 {
-        // the rows that pawn begin with, relative index 1 is index 0
         // if the pawn move 2 squares it must remain in the same column
-        return [1, 6].contains(&src.x) && v.y == 0;
+        return v.y == 0;
     }
     true
 }
@@ -0,0 +1,258 @@
+use std::fmt::{self, Formatter};
+
+use super::board_manager::{algebraic_notation_letters_formatted, opposite_color, BoardManager};
+use super::piece::{Color, Piece, Position};
+
+/// how the board is drawn as text
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum Renderer {
+    /// the unicode chess glyphs
+    #[default]
+    Glyphs,
+    /// the fen letters, uppercase for white and lowercase for black
+    Letters,
+    /// the fen letters inside ascii boxes, readable in any log
+    AsciiBoxes,
+    /// the unicode chess glyphs given two cells, for terminals that draw them wide
+    WideGlyphs,
+}
+
+pub const RENDERERS: [Renderer; 4] = [
+    Renderer::Glyphs,
+    Renderer::Letters,
+    Renderer::AsciiBoxes,
+    Renderer::WideGlyphs,
+];
+
+/// which side of the board is shown at the bottom
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum Orientation {
+    /// white at the bottom whoever is to move
+    #[default]
+    White,
+    /// black at the bottom whoever is to move
+    Black,
+    /// the side to move at the bottom, the board turns after every move
+    SideToMove,
+    /// the side of the human player at the bottom, kept when a new game starts
+    Player(Color),
+}
+
+pub const ORIENTATIONS: [Orientation; 4] = [
+    Orientation::White,
+    Orientation::Black,
+    Orientation::SideToMove,
+    Orientation::Player(Color::White),
+];
+
+impl Orientation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Orientation::White => "white",
+            Orientation::Black => "black",
+            Orientation::SideToMove => "side",
+            Orientation::Player(_) => "player",
+        }
+    }
+
+    /// the orientation by its name, case insensitive, player can be followed by
+    /// the color of the player and is white without it
+    pub fn from_name(name: &str) -> Option<Orientation> {
+        let name = name.to_lowercase();
+        let mut words = name.split_whitespace();
+        let orientation = match (words.next()?, words.next()) {
+            ("white", None) => Orientation::White,
+            ("black", None) => Orientation::Black,
+            ("side", None) => Orientation::SideToMove,
+            ("player", None | Some("white")) => Orientation::Player(Color::White),
+            ("player", Some("black")) => Orientation::Player(Color::Black),
+            _ => return None,
+        };
+        words.next().is_none().then_some(orientation)
+    }
+
+    /// the color shown at the bottom of the board
+    pub fn bottom(&self, board: &BoardManager) -> Color {
+        match self {
+            Orientation::White => Color::White,
+            Orientation::Black => Color::Black,
+            Orientation::SideToMove => board.side_to_move(),
+            Orientation::Player(color) => *color,
+        }
+    }
+
+    /// the orientation that shows the board turned around, following the side
+    /// to move turns into the fixed side that is at the top now
+    pub fn flipped(&self, board: &BoardManager) -> Orientation {
+        match self {
+            Orientation::Player(color) => Orientation::Player(opposite_color(*color)),
+            _ => match opposite_color(self.bottom(board)) {
+                Color::White => Orientation::White,
+                Color::Black => Orientation::Black,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Orientation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Orientation::Player(color) => f.pad(&format!("player {:?}", color).to_lowercase()),
+            _ => f.pad(self.name()),
+        }
+    }
+}
+
+impl Renderer {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Renderer::Glyphs => "glyphs",
+            Renderer::Letters => "letters",
+            Renderer::AsciiBoxes => "boxes",
+            Renderer::WideGlyphs => "wide",
+        }
+    }
+
+    /// the renderer by its name, case insensitive
+    pub fn from_name(name: &str) -> Option<Renderer> {
+        let name = name.to_lowercase();
+        RENDERERS
+            .iter()
+            .copied()
+            .find(|renderer| renderer.name() == name)
+    }
+
+    /// the character the piece is drawn with
+    pub fn piece_char(&self, piece: &Piece) -> char {
+        match self {
+            Renderer::Glyphs | Renderer::WideGlyphs => piece.to_string().chars().next().unwrap(),
+            Renderer::Letters | Renderer::AsciiBoxes => piece.to_fen_char(),
+        }
+    }
+
+    /// the board drawn with the orientation
+    pub fn render(&self, board: &BoardManager, orientation: Orientation) -> String {
+        struct Drawing<'a>(Renderer, &'a BoardManager, Orientation);
+
+        impl fmt::Display for Drawing<'_> {
+            fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                self.0.write_board(self.1, self.2, f)
+            }
+        }
+
+        Drawing(*self, board, orientation).to_string()
+    }
+
+    /// write the board with the column letters above and below it
+    pub fn write_board(
+        &self,
+        board: &BoardManager,
+        orientation: Orientation,
+        f: &mut Formatter,
+    ) -> fmt::Result {
+        let white_in_front = orientation.bottom(board) == Color::White;
+        match self {
+            Renderer::Glyphs => {
+                algebraic_notation_letters_formatted(f, &board.size());
+                if white_in_front {
+                    board.output_white_front(f)?;
+                } else {
+                    board.output_black_front(f)?;
+                }
+                algebraic_notation_letters_formatted(f, &board.size());
+                Ok(())
+            }
+            Renderer::Letters => {
+                self.write_rows(board, white_in_front, f, "  ", |piece| match piece {
+                    Some(piece) => format!(" {}", piece.to_fen_char()),
+                    None => " .".to_string(),
+                })
+            }
+            Renderer::WideGlyphs => {
+                self.write_rows(board, white_in_front, f, "  ", |piece| match piece {
+                    // the glyph takes the two cells after the space
+                    Some(piece) => format!(" {}", piece),
+                    None => " · ".to_string(),
+                })
+            }
+            Renderer::AsciiBoxes => self.write_boxes(board, white_in_front, f),
+        }
+    }
+
+    /// the rows in the order they are shown, the front row last
+    fn shown_rows(board: &BoardManager, white_in_front: bool) -> Vec<i8> {
+        let row_count = board.size().row_count() as i8;
+        if white_in_front {
+            (0..row_count).rev().collect()
+        } else {
+            (0..row_count).collect()
+        }
+    }
+
+    fn write_letters(
+        board: &BoardManager,
+        f: &mut Formatter,
+        prefix: &str,
+        width: usize,
+    ) -> fmt::Result {
+        write!(f, "{}", prefix)?;
+        for letter in (b'a'..b'a' + board.size().column_count() as u8).map(char::from) {
+            write!(f, "{:<width$}", format!(" {}", letter), width = width)?;
+        }
+        Ok(())
+    }
+
+    /// the row number and the text of every square of the row
+    fn write_rows(
+        &self,
+        board: &BoardManager,
+        white_in_front: bool,
+        f: &mut Formatter,
+        prefix: &str,
+        square: impl Fn(Option<Piece>) -> String,
+    ) -> fmt::Result {
+        let width = square(None).chars().count();
+        Renderer::write_letters(board, f, prefix, width)?;
+        writeln!(f)?;
+        for row in Renderer::shown_rows(board, white_in_front) {
+            write!(f, "{:>2}", row + 1)?;
+            for column in 0..board.size().column_count() as i8 {
+                write!(f, "{}", square(board.piece_at(&Position::new(row, column))))?;
+            }
+            writeln!(f)?;
+        }
+        Renderer::write_letters(board, f, prefix, width)
+    }
+
+    /// every square is a box of ascii lines with the fen letter of its piece inside
+    fn write_boxes(
+        &self,
+        board: &BoardManager,
+        white_in_front: bool,
+        f: &mut Formatter,
+    ) -> fmt::Result {
+        let column_count = board.size().column_count();
+        let separator = format!("   {}+", "+---".repeat(column_count));
+        Renderer::write_letters(board, f, "    ", 4)?;
+        writeln!(f)?;
+        writeln!(f, "{}", separator)?;
+        for row in Renderer::shown_rows(board, white_in_front) {
+            write!(f, "{:>2} ", row + 1)?;
+            for column in 0..column_count as i8 {
+                let letter = board
+                    .piece_at(&Position::new(row, column))
+                    .map_or(' ', |piece| piece.to_fen_char());
+                write!(f, "| {} ", letter)?;
+            }
+            writeln!(f, "| {}", row + 1)?;
+            writeln!(f, "{}", separator)?;
+        }
+        Renderer::write_letters(board, f, "    ", 4)
+    }
+}
+
+impl fmt::Display for Renderer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
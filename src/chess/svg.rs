@@ -0,0 +1,197 @@
+use std::fmt::Write;
+
+use super::board_manager::BoardManager;
+use super::piece::{Color, Piece, Position};
+use super::renderer::Orientation;
+
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+const HIGHLIGHT: &str = "#9bc700";
+const ARROW: &str = "#15781b";
+const FONT_FAMILY: &str = "'DejaVu Sans', 'Segoe UI Symbol', 'Noto Sans Symbols2', serif";
+
+/// what the diagram shows besides the pieces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramOptions {
+    /// the width of a square in pixels
+    pub square_size: u32,
+    pub orientation: Orientation,
+    /// the column letters and row numbers around the board
+    pub coordinates: bool,
+    /// the squares painted over their color
+    pub highlights: Vec<Position>,
+    /// arrows from the first square to the second
+    pub arrows: Vec<(Position, Position)>,
+}
+
+impl Default for DiagramOptions {
+    fn default() -> Self {
+        DiagramOptions {
+            square_size: 45,
+            orientation: Orientation::White,
+            coordinates: true,
+            highlights: Vec::new(),
+            arrows: Vec::new(),
+        }
+    }
+}
+
+/// the board as a self-contained svg image, the pieces are the unicode glyphs so the
+/// diagram needs no images or fonts of its own. with black at the bottom the columns
+/// are mirrored too, like on a real board
+pub fn render_svg(board: &BoardManager, options: &DiagramOptions) -> String {
+    let size = board.size();
+    let square = options.square_size.max(1) as f32;
+    let margin = if options.coordinates {
+        square / 2.0
+    } else {
+        0.0
+    };
+    let width = square * size.column_count() as f32 + 2.0 * margin;
+    let height = square * size.row_count() as f32 + 2.0 * margin;
+    let white_at_bottom = options.orientation.bottom(board) == Color::White;
+
+    // the top left corner of the square in the image
+    let corner = |position: &Position| -> (f32, f32) {
+        let (line, column) = if white_at_bottom {
+            (size.last_row() - position.x, position.y)
+        } else {
+            (position.x, size.last_column() - position.y)
+        };
+        (
+            margin + column as f32 * square,
+            margin + line as f32 * square,
+        )
+    };
+    let center = |position: &Position| {
+        let (x, y) = corner(position);
+        (x + square / 2.0, y + square / 2.0)
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<defs><marker id="arrowhead" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="3" markerHeight="3" orient="auto"><path d="M0,0 L10,5 L0,10 z" fill="{}"/></marker></defs>"#,
+        ARROW
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="{}" height="{}" fill="{}"/>"#,
+        width, height, DARK_SQUARE
+    );
+
+    for row in 0..size.row_count() as i8 {
+        for column in 0..size.column_count() as i8 {
+            let position = Position::new(row, column);
+            let (x, y) = corner(&position);
+            let fill = if (row + column) % 2 == 1 {
+                LIGHT_SQUARE
+            } else {
+                DARK_SQUARE
+            };
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x, y, square, square, fill
+            );
+            if options.highlights.contains(&position) {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.5"/>"#,
+                    x, y, square, square, HIGHLIGHT
+                );
+            }
+        }
+    }
+
+    if options.coordinates {
+        let font_size = square * 0.3;
+        for column in 0..size.column_count() as i8 {
+            let (x, _) = center(&Position::new(0, column));
+            let letter = (b'a' + column as u8) as char;
+            for y in [margin / 2.0, height - margin / 2.0] {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    x, y, font_size, LIGHT_SQUARE, letter
+                );
+            }
+        }
+        for row in 0..size.row_count() as i8 {
+            let (_, y) = center(&Position::new(row, 0));
+            for x in [margin / 2.0, width - margin / 2.0] {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    x,
+                    y,
+                    font_size,
+                    LIGHT_SQUARE,
+                    row + 1
+                );
+            }
+        }
+    }
+
+    for row in 0..size.row_count() as i8 {
+        for column in 0..size.column_count() as i8 {
+            let position = Position::new(row, column);
+            let Some(piece) = board.piece_at(&position) else {
+                continue;
+            };
+            let (x, y) = center(&position);
+            // the filled glyphs of black drawn in both colors, with an outline for white,
+            // the variation selector keeps viewers from drawing the pawn as an emoji
+            let text = if piece.p_type.is_fairy() {
+                piece.p_type.to_fen_char().to_ascii_uppercase().to_string()
+            } else {
+                format!("{}\u{FE0E}", Piece::new(piece.p_type, Color::Black))
+            };
+            let (fill, stroke) = match piece.p_color {
+                Color::White => ("#ffffff", "#000000"),
+                Color::Black => ("#000000", "#ffffff"),
+            };
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" stroke="{}" stroke-width="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x,
+                y,
+                FONT_FAMILY,
+                square * 0.8,
+                fill,
+                stroke,
+                square / 40.0,
+                text
+            );
+        }
+    }
+
+    for (from, to) in &options.arrows {
+        let (x1, y1) = center(from);
+        let (x2, y2) = center(to);
+        // the head ends inside the destination square instead of covering its piece
+        let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1.0);
+        let shorten = square * 0.3;
+        let x2 = x2 - (x2 - x1) / length * shorten;
+        let y2 = y2 - (y2 - y1) / length * shorten;
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-opacity="0.8" stroke-linecap="round" marker-end="url(#arrowhead)"/>"#,
+            x1,
+            y1,
+            x2,
+            y2,
+            ARROW,
+            square / 6.0
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
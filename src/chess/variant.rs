@@ -0,0 +1,419 @@
+use std::fmt;
+use std::rc::Rc;
+
+use super::board_manager::{opposite_color, BoardManager, BoardSizeInfo, GameStatus};
+use super::error::MoveError;
+use super::parse::ChessMove;
+use super::piece::{Color, PieceType, Position};
+
+/// the checks a player has to give to win three-check
+pub const CHECKS_TO_WIN: u8 = 3;
+
+/// the fen string of the standard chess start position
+pub const STANDARD_START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// the rules a game is played by, every method but the name defaults to the standard chess rules
+/// so a house rule only overrides what it changes
+pub trait Variant: fmt::Debug {
+    /// the name used to pick the variant in commands
+    fn name(&self) -> &str;
+
+    /// the name shown to the players
+    fn title(&self) -> &str {
+        self.name()
+    }
+
+    /// the fen string of the position the games of the variant start from
+    fn start_fen(&self) -> &str {
+        STANDARD_START_FEN
+    }
+
+    /// the variant starts from the standard position, so it can start from a Fischer random one too
+    fn has_standard_start(&self) -> bool {
+        self.start_fen() == STANDARD_START_FEN
+    }
+
+    /// the king must be kept out of check
+    fn has_king_safety(&self) -> bool {
+        true
+    }
+
+    /// a position without a king of the color is invalid
+    fn requires_king(&self, _color: Color) -> bool {
+        true
+    }
+
+    fn allows_castling(&self) -> bool {
+        true
+    }
+
+    /// pawns on the first row may move two squares too
+    fn has_first_row_double_step(&self) -> bool {
+        false
+    }
+
+    /// the piece types a pawn can promote to
+    fn can_promote_to(&self, p_type: PieceType) -> bool {
+        !matches!(p_type, PieceType::Pawn | PieceType::King)
+    }
+
+    /// captures explode the pieces around them, so the king can't capture
+    fn captures_explode(&self) -> bool {
+        false
+    }
+
+    /// captured pieces go to the pocket of the captor, the pockets are part of the fen string
+    fn has_pockets(&self) -> bool {
+        false
+    }
+
+    /// the checks given are counted, the counters are part of the fen string
+    fn counts_checks(&self) -> bool {
+        false
+    }
+
+    /// the pins and checks can't tell if a move is legal, every move is tried on the board
+    fn needs_trial_moves(&self) -> bool {
+        false
+    }
+
+    /// extra rules for a single move, checked before it is tried on the board
+    fn validate_move(
+        &self,
+        _board: &mut BoardManager,
+        _chess_move: &ChessMove,
+    ) -> Result<(), MoveError> {
+        Ok(())
+    }
+
+    /// extra rules for the position after the move, the move is on the board
+    /// and the king of the mover is safe
+    fn validate_position(&self, _board: &BoardManager, _mover: Color) -> Result<(), MoveError> {
+        Ok(())
+    }
+
+    /// remove the moves the variant doesn't allow from the legal moves of the side to move
+    fn filter_moves(&self, _board: &BoardManager, _legal_moves: &mut Vec<ChessMove>) {}
+
+    /// the end of the game by the rules of the variant, the game ends even if there are moves left
+    fn game_status(&self, _board: &mut BoardManager) -> Option<GameStatus> {
+        None
+    }
+
+    /// the end of the game when the side to move has no legal moves,
+    /// None leaves it to checkmate and stalemate
+    fn status_without_moves(&self, _board: &BoardManager) -> Option<GameStatus> {
+        None
+    }
+
+    /// change the score the engine gave the position, from the point of view of white
+    fn adjust_score(&self, _board: &BoardManager, score: i32) -> i32 {
+        score
+    }
+}
+
+/// the variants that come with the crate
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum VariantKind {
+    #[default]
+    Standard,
+    /// giving check for the third time wins the game
+    ThreeCheck,
+    /// bringing the king to one of the four center squares wins the game
+    KingOfTheHill,
+    /// captures explode every piece around them but the pawns, exploding the king wins the game
+    Atomic,
+    /// captured pieces join the pocket of the captor and can be dropped back on the board
+    Crazyhouse,
+    /// captures are compulsory and the king is an ordinary piece, losing every piece wins the game
+    Antichess,
+    /// white has a horde of pawns and no king, black wins by capturing all of it
+    Horde,
+    /// no checks are allowed, the first king to reach the last row wins the game
+    RacingKings,
+}
+
+/// every variant, in the order they are listed in the mode menu
+pub const VARIANTS: [VariantKind; 8] = [
+    VariantKind::Standard,
+    VariantKind::ThreeCheck,
+    VariantKind::KingOfTheHill,
+    VariantKind::Atomic,
+    VariantKind::Crazyhouse,
+    VariantKind::Antichess,
+    VariantKind::Horde,
+    VariantKind::RacingKings,
+];
+
+impl VariantKind {
+    /// a line about the rules of the variant
+    pub fn description(&self) -> &'static str {
+        match self {
+            VariantKind::Standard => "the usual chess rules",
+            VariantKind::ThreeCheck => "giving check for the third time wins",
+            VariantKind::KingOfTheHill => "bringing the king to d4, d5, e4 or e5 wins",
+            VariantKind::Atomic => "captures explode the pieces around, exploding the king wins",
+            VariantKind::Crazyhouse => "captured pieces can be dropped back on the board",
+            VariantKind::Antichess => "captures are a must, losing every piece or being stuck wins",
+            VariantKind::Horde => "36 white pawns against the black army, capturing them all wins",
+            VariantKind::RacingKings => "no checks, the first king to reach the last row wins",
+        }
+    }
+
+    /// find the variant by its name or by its number in the mode menu, starting at 1
+    pub fn from_name(name: &str) -> Option<VariantKind> {
+        let name = name.to_lowercase();
+        if let Ok(number) = name.parse::<usize>() {
+            return VARIANTS.get(number.checked_sub(1)?).copied();
+        }
+        match name.as_str() {
+            "3check" | "3-check" | "three-check" => Some(VariantKind::ThreeCheck),
+            "koth" | "king-of-the-hill" => Some(VariantKind::KingOfTheHill),
+            "zh" => Some(VariantKind::Crazyhouse),
+            "losing" | "losingchess" | "giveaway" => Some(VariantKind::Antichess),
+            "racing" | "racing-kings" => Some(VariantKind::RacingKings),
+            _ => VARIANTS
+                .iter()
+                .find(|variant| variant.name() == name)
+                .copied(),
+        }
+    }
+}
+
+impl Variant for VariantKind {
+    fn name(&self) -> &str {
+        match self {
+            VariantKind::Standard => "standard",
+            VariantKind::ThreeCheck => "threecheck",
+            VariantKind::KingOfTheHill => "kingofthehill",
+            VariantKind::Atomic => "atomic",
+            VariantKind::Crazyhouse => "crazyhouse",
+            VariantKind::Antichess => "antichess",
+            VariantKind::Horde => "horde",
+            VariantKind::RacingKings => "racingkings",
+        }
+    }
+
+    fn title(&self) -> &str {
+        match self {
+            VariantKind::Standard => "Standard",
+            VariantKind::ThreeCheck => "Three-Check",
+            VariantKind::KingOfTheHill => "King Of The Hill",
+            VariantKind::Atomic => "Atomic",
+            VariantKind::Crazyhouse => "Crazyhouse",
+            VariantKind::Antichess => "Antichess",
+            VariantKind::Horde => "Horde",
+            VariantKind::RacingKings => "Racing Kings",
+        }
+    }
+
+    fn start_fen(&self) -> &str {
+        match self {
+            VariantKind::Horde => {
+                "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+            }
+            VariantKind::RacingKings => "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+            _ => STANDARD_START_FEN,
+        }
+    }
+
+    fn has_king_safety(&self) -> bool {
+        *self != VariantKind::Antichess
+    }
+
+    fn requires_king(&self, color: Color) -> bool {
+        match self {
+            VariantKind::Antichess => false,
+            VariantKind::Horde => color == Color::Black,
+            _ => true,
+        }
+    }
+
+    fn allows_castling(&self) -> bool {
+        !matches!(self, VariantKind::Antichess | VariantKind::RacingKings)
+    }
+
+    fn has_first_row_double_step(&self) -> bool {
+        *self == VariantKind::Horde
+    }
+
+    fn can_promote_to(&self, p_type: PieceType) -> bool {
+        match p_type {
+            PieceType::Pawn => false,
+            // the king is an ordinary piece in antichess
+            PieceType::King => *self == VariantKind::Antichess,
+            _ => true,
+        }
+    }
+
+    fn captures_explode(&self) -> bool {
+        *self == VariantKind::Atomic
+    }
+
+    fn has_pockets(&self) -> bool {
+        *self == VariantKind::Crazyhouse
+    }
+
+    fn counts_checks(&self) -> bool {
+        *self == VariantKind::ThreeCheck
+    }
+
+    fn needs_trial_moves(&self) -> bool {
+        // an explosion can take the pieces that guard the king,
+        // and without checks any move can uncover one
+        matches!(self, VariantKind::Atomic | VariantKind::RacingKings)
+    }
+
+    fn validate_move(
+        &self,
+        board: &mut BoardManager,
+        chess_move: &ChessMove,
+    ) -> Result<(), MoveError> {
+        if *self == VariantKind::Antichess
+            && !board.is_capture(chess_move)
+            && board
+                .legal_moves()
+                .iter()
+                .any(|legal_move| board.is_capture(legal_move))
+        {
+            return Err(MoveError::CaptureRequired);
+        }
+        Ok(())
+    }
+
+    fn validate_position(&self, board: &BoardManager, mover: Color) -> Result<(), MoveError> {
+        if *self == VariantKind::RacingKings && board.is_check(opposite_color(mover)) {
+            return Err(MoveError::GivesCheck);
+        }
+        Ok(())
+    }
+
+    fn filter_moves(&self, board: &BoardManager, legal_moves: &mut Vec<ChessMove>) {
+        if *self == VariantKind::Antichess
+            && legal_moves
+                .iter()
+                .any(|chess_move| board.is_capture(chess_move))
+        {
+            legal_moves.retain(|chess_move| board.is_capture(chess_move));
+        }
+    }
+
+    fn game_status(&self, board: &mut BoardManager) -> Option<GameStatus> {
+        let colors = [Color::White, Color::Black];
+        match self {
+            // antichess ends by the legal moves, it is checked in status_without_moves
+            VariantKind::Standard | VariantKind::Crazyhouse | VariantKind::Antichess => None,
+            VariantKind::ThreeCheck => colors
+                .into_iter()
+                .find(|color| board.checks_given().given_by(*color) >= CHECKS_TO_WIN)
+                .map(|winner| GameStatus::ThirdCheck { winner }),
+            VariantKind::KingOfTheHill => colors
+                .into_iter()
+                .find(|color| {
+                    board.has_king(*color)
+                        && is_hill_square(&board.king_position(*color), &board.size())
+                })
+                .map(|winner| GameStatus::KingOfTheHill { winner }),
+            VariantKind::Atomic => colors
+                .into_iter()
+                .find(|color| !board.has_king(*color))
+                .map(|loser| GameStatus::KingExploded {
+                    winner: opposite_color(loser),
+                }),
+            VariantKind::Horde => {
+                let size = board.size();
+                let white_has_pieces = (0..size.row_count() as i8).any(|row| {
+                    (0..size.column_count() as i8).any(|column| {
+                        board
+                            .piece_at(&Position::new(row, column))
+                            .is_some_and(|piece| piece.p_color == Color::White)
+                    })
+                });
+                (!white_has_pieces).then_some(GameStatus::AllPiecesCaptured {
+                    winner: Color::Black,
+                })
+            }
+            VariantKind::RacingKings => race_status(board),
+        }
+    }
+
+    fn status_without_moves(&self, board: &BoardManager) -> Option<GameStatus> {
+        (*self == VariantKind::Antichess).then_some(GameStatus::OutOfMoves {
+            winner: board.side_to_move(),
+        })
+    }
+
+    fn adjust_score(&self, board: &BoardManager, score: i32) -> i32 {
+        match self {
+            VariantKind::KingOfTheHill => {
+                let bonus = |color| match board.has_king(color) {
+                    true => hill_bonus(&board.king_position(color), &board.size()),
+                    false => 0,
+                };
+                score + bonus(Color::White) - bonus(Color::Black)
+            }
+            // in antichess losing the pieces is the goal
+            VariantKind::Antichess => -score,
+            _ => score,
+        }
+    }
+}
+
+impl fmt::Display for VariantKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.title())
+    }
+}
+
+impl From<VariantKind> for Rc<dyn Variant> {
+    fn from(variant: VariantKind) -> Self {
+        Rc::new(variant)
+    }
+}
+
+/// the first king on the last row wins racing kings, but when white gets there first
+/// black still has a move to reach it too and draw
+fn race_status(board: &mut BoardManager) -> Option<GameStatus> {
+    let last_row = board.size().last_row();
+    let white_arrived = board.king_position(Color::White).x == last_row;
+    let black_arrived = board.king_position(Color::Black).x == last_row;
+    match (white_arrived, black_arrived) {
+        (true, true) => Some(GameStatus::RaceDrawn),
+        (false, true) => Some(GameStatus::GoalReached {
+            winner: Color::Black,
+        }),
+        (true, false) => {
+            let black_king = board.king_position(Color::Black);
+            let black_can_arrive = board.side_to_move() == Color::Black
+                && board
+                    .unfiltered_moves_from(&black_king)
+                    .iter()
+                    .any(|chess_move| chess_move.piece_dest.x == last_row);
+            (!black_can_arrive).then_some(GameStatus::GoalReached {
+                winner: Color::White,
+            })
+        }
+        (false, false) => None,
+    }
+}
+
+/// the number of king steps from the position to the nearest center square
+pub fn distance_to_hill(position: &Position, size: &BoardSizeInfo) -> i8 {
+    // the two middle rows or columns on even counts, the single middle one on odd counts
+    let distance = |value: i8, count: usize| {
+        let (first_center, last_center) = ((count as i8 - 1) / 2, count as i8 / 2);
+        (first_center - value).max(value - last_center).max(0)
+    };
+    distance(position.x, size.row_count()).max(distance(position.y, size.column_count()))
+}
+
+/// check if the position is one of the center squares of King of the Hill
+pub fn is_hill_square(position: &Position, size: &BoardSizeInfo) -> bool {
+    distance_to_hill(position, size) == 0
+}
+
+/// in King of the Hill the king is worth more the closer it gets to the center
+fn hill_bonus(position: &Position, size: &BoardSizeInfo) -> i32 {
+    let farthest = ((size.row_count().max(size.column_count()) - 1) / 2) as i32;
+    (farthest - distance_to_hill(position, size) as i32) * 40
+}
@@ -0,0 +1,71 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::session::GameSession;
+
+type MyResult<T> = Result<T, Box<dyn Error>>;
+
+/// the file of the game saved after every move, in the data directory
+const AUTOSAVE_FILE: &str = "autosave.pgn";
+
+/// the directory of the files kept for the user, CTE_DATA_DIR when it is set and
+/// otherwise the data directory of the platform
+pub fn data_dir() -> Option<PathBuf> {
+    let non_empty = |name| env::var_os(name).filter(|value| !value.is_empty());
+    if let Some(dir) = non_empty("CTE_DATA_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let base = if cfg!(windows) {
+        PathBuf::from(non_empty("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(non_empty("HOME")?).join("Library/Application Support")
+    } else {
+        match non_empty("XDG_DATA_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(non_empty("HOME")?).join(".local/share"),
+        }
+    };
+    Some(base.join("cte"))
+}
+
+/// the file the game is saved to after every move
+pub fn autosave_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(AUTOSAVE_FILE))
+}
+
+/// save the game as pgn to the file, written next to it first and then renamed
+/// so a closed terminal never leaves half a file
+pub fn save_to(path: &Path, session: &GameSession) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("pgn.partial");
+    fs::write(&partial, session.to_pgn())?;
+    fs::rename(&partial, path)
+}
+
+/// restore the game saved to the file
+pub fn resume_from(path: &Path) -> MyResult<GameSession> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Can't Read The Saved Game {}, {}", path.display(), err))?;
+    GameSession::from_pgn(&text)
+}
+
+/// save the game to the data directory
+pub fn autosave(session: &GameSession) -> MyResult<()> {
+    let path = autosave_path().ok_or("No Data Directory, Set HOME Or CTE_DATA_DIR")?;
+    save_to(&path, session)?;
+    Ok(())
+}
+
+/// restore the game saved to the data directory
+pub fn resume() -> MyResult<GameSession> {
+    let path = autosave_path().ok_or("No Data Directory, Set HOME Or CTE_DATA_DIR")?;
+    if !path.is_file() {
+        Err(format!("No Saved Game To Resume In {}", path.display()))?;
+    }
+    resume_from(&path)
+}
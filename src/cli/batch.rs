@@ -0,0 +1,188 @@
+use std::fs;
+use std::io::{BufRead, Write};
+
+use crate::chess::board_manager::BoardManager;
+use crate::chess::error::FenError;
+use crate::chess::parse::{parse_chess_move, ChessMove};
+use crate::chess::pgn::parse_san;
+use crate::chess::variant::VariantKind;
+use crate::GameOptions;
+
+use super::command::{parse_command, Command};
+use super::render::Theme;
+use super::session::GameSession;
+
+/// the exit code when every move and command went through
+pub const EXIT_SUCCESS: i32 = 0;
+/// the exit code when a move is illegal or a command of the script failed
+pub const EXIT_ILLEGAL_MOVE: i32 = 1;
+/// the exit code of wrong arguments, a bad fen string or a script that can't be read
+pub const EXIT_USAGE: i32 = 2;
+
+/// what the batch mode prints about the position it reached
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Report {
+    Fen,
+    Board,
+    /// the result or the side to move
+    Status,
+    /// the legal moves in uci notation
+    Legal,
+}
+
+pub const REPORTS: [Report; 4] = [Report::Fen, Report::Board, Report::Status, Report::Legal];
+
+impl Report {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Report::Fen => "fen",
+            Report::Board => "board",
+            Report::Status => "status",
+            Report::Legal => "legal",
+        }
+    }
+
+    /// the report by its name, case insensitive
+    pub fn from_name(name: &str) -> Option<Report> {
+        let name = name.to_lowercase();
+        REPORTS.iter().copied().find(|report| report.name() == name)
+    }
+
+    /// the text of the report about the game
+    pub fn text(&self, session: &GameSession) -> String {
+        match self {
+            Report::Fen => session.board().to_fen(),
+            Report::Board => session.render_board(),
+            Report::Status => session.status(),
+            Report::Legal => {
+                let mut board = session.board().clone();
+                let legal_moves: Vec<String> = board
+                    .legal_moves()
+                    .iter()
+                    .map(|chess_move| board.move_to_uci(chess_move))
+                    .collect();
+                legal_moves.join(" ")
+            }
+        }
+    }
+}
+
+/// play the moves and run the script of the options without asking anything, then write
+/// the reports, the fen string when none was asked for. errors go to the standard error
+/// and the returned exit code tells what went wrong
+pub fn run_batch(options: &GameOptions, input: &mut impl BufRead, output: &mut impl Write) -> i32 {
+    let board = match start_board(options.fen.as_deref(), options.mode) {
+        Ok(board) => board,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return EXIT_USAGE;
+        }
+    };
+    let mut session = GameSession::from_board(board);
+    session.set_theme(Theme::Plain);
+    session.apply_settings(options.theme, options.renderer, options.orientation);
+
+    let mut exit_code = play_moves(&mut session, &options.moves);
+    if exit_code == EXIT_SUCCESS {
+        if let Some(script) = &options.script {
+            exit_code = run_script(&mut session, script, input, output);
+        }
+    }
+
+    let reports = if options.reports.is_empty() {
+        &[Report::Fen][..]
+    } else {
+        &options.reports[..]
+    };
+    for report in reports {
+        if writeln!(output, "{}", report.text(&session)).is_err() {
+            return EXIT_USAGE;
+        }
+    }
+    exit_code
+}
+
+/// the position of the fen string or the start position, in the variant
+pub(crate) fn start_board(
+    fen: Option<&str>,
+    mode: Option<VariantKind>,
+) -> Result<BoardManager, FenError> {
+    let variant = mode.unwrap_or_default();
+    match fen {
+        Some(fen_string) => BoardManager::try_from_variant_fen(fen_string, variant),
+        None => Ok(BoardManager::new_variant(variant)),
+    }
+}
+
+/// the move written in uci or in standard algebraic notation
+pub(crate) fn find_move(board: &BoardManager, text: &str) -> Option<ChessMove> {
+    parse_chess_move(text)
+        .ok()
+        .or_else(|| parse_san(&mut board.clone(), text))
+}
+
+pub(crate) fn play_moves(session: &mut GameSession, moves: &[String]) -> i32 {
+    for (number, text) in moves.iter().enumerate() {
+        let played = match find_move(session.board(), text) {
+            Some(chess_move) => session.execute(Command::Move(chess_move)).map(|_| ()),
+            None => Err("It Is Not A Legal Move".into()),
+        };
+        if let Err(err) = played {
+            eprintln!(
+                "Error: Move {} '{}' Was Not Played, {}",
+                number + 1,
+                text,
+                err
+            );
+            return EXIT_ILLEGAL_MOVE;
+        }
+    }
+    EXIT_SUCCESS
+}
+
+/// run the commands of the script line by line, empty lines and lines starting with #
+/// are skipped and quit ends the script
+fn run_script(
+    session: &mut GameSession,
+    script: &str,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> i32 {
+    let text = if script == "-" {
+        let mut text = String::new();
+        input.read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(script)
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Error: Can't Read The Script {}, {}", script, err);
+            return EXIT_USAGE;
+        }
+    };
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let result = parse_command(line).and_then(|command| match command {
+            Command::Quit => Ok(None),
+            command => session.execute(command).map(Some),
+        });
+        match result {
+            Ok(Some(text)) => {
+                if writeln!(output, "{}", text).is_err() {
+                    return EXIT_USAGE;
+                }
+            }
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("Error: Script Line {} '{}', {}", number + 1, line, err);
+                return EXIT_ILLEGAL_MOVE;
+            }
+        }
+    }
+    EXIT_SUCCESS
+}
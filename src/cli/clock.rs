@@ -0,0 +1,160 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::chess::piece::Color;
+
+/// the time each player has for the game and the time added after each of their moves
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct TimeControl {
+    pub initial: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// read minutes followed by an optional increment in seconds, as in 5+3 or 0.5,
+    /// both are rounded to whole seconds
+    pub fn from_name(name: &str) -> Option<TimeControl> {
+        let (minutes, increment) = name.split_once('+').unwrap_or((name, "0"));
+        let minutes: f64 = minutes.trim().parse().ok()?;
+        let increment: f64 = increment.trim().parse().ok()?;
+        let whole_seconds = |seconds: f64| Duration::try_from_secs_f64(seconds.round()).ok();
+        let time_control = TimeControl {
+            initial: whole_seconds(minutes * 60.0)?,
+            increment: whole_seconds(increment)?,
+        };
+        (!time_control.initial.is_zero()).then_some(time_control)
+    }
+
+    /// the value of the pgn TimeControl tag, seconds followed by the increment, as in 300+3
+    pub fn to_pgn_tag(&self) -> String {
+        format!("{}+{}", self.initial.as_secs(), self.increment.as_secs())
+    }
+
+    pub fn from_pgn_tag(tag: &str) -> Option<TimeControl> {
+        let (seconds, increment) = tag.split_once('+').unwrap_or((tag, "0"));
+        let time_control = TimeControl {
+            initial: Duration::from_secs(seconds.trim().parse().ok()?),
+            increment: Duration::from_secs(increment.trim().parse().ok()?),
+        };
+        (!time_control.initial.is_zero()).then_some(time_control)
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}+{}",
+            self.initial.as_secs_f64() / 60.0,
+            self.increment.as_secs_f64()
+        )
+    }
+}
+
+/// the time each player spent on their moves and what is left of it under a time control
+#[derive(Debug, Clone)]
+pub struct Clocks {
+    white: Duration,
+    black: Duration,
+    turn_started: Instant,
+    time_control: Option<TimeControl>,
+    /// the increments each player got so far
+    white_bonus: Duration,
+    black_bonus: Duration,
+}
+
+impl Default for Clocks {
+    fn default() -> Self {
+        Clocks::with_used(Duration::ZERO, Duration::ZERO)
+    }
+}
+
+impl Clocks {
+    pub fn new() -> Self {
+        Clocks::default()
+    }
+
+    /// the clocks of a game the players already spent time on, the running turn starts now
+    pub fn with_used(white: Duration, black: Duration) -> Self {
+        Clocks {
+            white,
+            black,
+            turn_started: Instant::now(),
+            time_control: None,
+            white_bonus: Duration::ZERO,
+            black_bonus: Duration::ZERO,
+        }
+    }
+
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.time_control
+    }
+
+    /// play under the time control, the players are given the increments of the moves
+    /// they already made
+    pub fn set_time_control(
+        &mut self,
+        time_control: TimeControl,
+        white_moves: u32,
+        black_moves: u32,
+    ) {
+        self.time_control = Some(time_control);
+        self.white_bonus = time_control.increment * white_moves;
+        self.black_bonus = time_control.increment * black_moves;
+    }
+
+    /// the time the color has left, None without a time control
+    pub fn remaining(&self, color: Color, side_to_move: Color) -> Option<Duration> {
+        let time_control = self.time_control?;
+        let bonus = match color {
+            Color::White => self.white_bonus,
+            Color::Black => self.black_bonus,
+        };
+        Some((time_control.initial + bonus).saturating_sub(self.used(color, side_to_move)))
+    }
+
+    /// the color used all of its time
+    pub fn is_out_of_time(&self, color: Color, side_to_move: Color) -> bool {
+        self.remaining(color, side_to_move)
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// the time left under a time control and otherwise the time used
+    pub fn shown(&self, color: Color, side_to_move: Color) -> Duration {
+        self.remaining(color, side_to_move)
+            .unwrap_or_else(|| self.used(color, side_to_move))
+    }
+
+    /// the time of the color, the running turn included when it is the color to move
+    pub fn used(&self, color: Color, side_to_move: Color) -> Duration {
+        let used = match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        };
+        if color == side_to_move {
+            used + self.turn_started.elapsed()
+        } else {
+            used
+        }
+    }
+
+    /// charge the mover with the time of the turn, give the increment and start the turn
+    /// of the opponent
+    pub fn end_turn(&mut self, mover: Color) {
+        let elapsed = self.turn_started.elapsed();
+        let increment = self
+            .time_control
+            .map_or(Duration::ZERO, |time_control| time_control.increment);
+        match mover {
+            Color::White => {
+                self.white += elapsed;
+                self.white_bonus += increment;
+            }
+            Color::Black => {
+                self.black += elapsed;
+                self.black_bonus += increment;
+            }
+        }
+        self.turn_started = Instant::now();
+    }
+}
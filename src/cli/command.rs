@@ -0,0 +1,212 @@
+use std::error::Error;
+
+use crate::chess::board_manager::BoardSizeInfo;
+use crate::chess::chess960;
+use crate::chess::parse::{parse_chess_move, parse_square, ChessMove};
+use crate::chess::piece::Position;
+use crate::chess::variant::VariantKind;
+
+use crate::chess::renderer::{Orientation, Renderer};
+
+use super::render::Theme;
+
+type MyResult<T> = Result<T, Box<dyn Error>>;
+
+pub const HELP_TEXT: &str = "\
+commands:
+  e2e4, e7e8q      make a move, castling is the king moving to the rook or two squares
+  N@f3             drop a piece from the pocket in crazyhouse
+  undo             take back the last move
+  redo             play again the last move that was taken back
+  flip             turn the board around
+  orientation      list the orientations, orientation <name> keeps white, black, the side to move or the player at the bottom
+  theme [name]     list the board color themes, or switch to the theme
+  pieces [name]    list the ways to draw the pieces, or switch to glyphs, letters, boxes or wide
+  new [960 [n]]    start a new game, 960 starts a Fischer random game from position n or a random one
+  new 6x6          start a new game on a board of 5 to 10 columns and rows, for example 5x5 or 10x8
+  mode [name]      list the game modes, or start a new game in the mode
+  fen              print the position as fen string
+  load <fen|file>  load a position from fen string or from a file
+  save <file>      save the position as fen string to a file
+  diagram <file>   save the board as svg image, squares like e4 and arrows like g1f3 can follow
+  moves [square]   list the legal moves, or only the moves of the piece on the square
+  hint             suggest a move
+  resign           give up the game
+  draw             offer a draw, or accept the draw offered by the opponent
+  uci              switch to the uci protocol used by chess programs
+  tui              switch to the full screen board, moved with the arrow keys
+  help             print this help
+  quit             exit the game";
+
+/// the start position of a new game
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StartPosition {
+    Standard,
+    /// Fischer random start position by its index, or a random one
+    Chess960(Option<usize>),
+    /// the start position of a board with other number of columns and rows
+    Sized(BoardSizeInfo),
+}
+
+/// a line of user input in the game
+#[derive(Debug, Clone)]
+pub enum Command {
+    Move(ChessMove),
+    Undo,
+    Redo,
+    Flip,
+    /// show the orientations, or turn the board to the orientation
+    Orientation(Option<Orientation>),
+    /// show the themes, or switch the board colors to the theme
+    Theme(Option<Theme>),
+    /// show the renderers, or draw the board with the renderer
+    Pieces(Option<Renderer>),
+    New(StartPosition),
+    /// show the mode menu, or start a new game played by the variant
+    Mode(Option<VariantKind>),
+    Fen,
+    Load(String),
+    Save(String),
+    /// save the board as svg image to the file, with the highlighted squares and the arrows
+    Diagram {
+        file: String,
+        highlights: Vec<Position>,
+        arrows: Vec<(Position, Position)>,
+    },
+    Moves(Option<Position>),
+    Hint,
+    Resign,
+    Draw,
+    Uci,
+    Tui,
+    Help,
+    Quit,
+}
+
+/// parse a line of user input to a command, anything that isn't a command keyword
+/// is parsed as a move
+pub fn parse_command(input: &str) -> MyResult<Command> {
+    let input = input.trim();
+    let (keyword, argument) = match input.split_once(char::is_whitespace) {
+        Some((keyword, argument)) => (keyword, argument.trim()),
+        None => (input, ""),
+    };
+
+    let command = match keyword.to_lowercase().as_str() {
+        "undo" => Command::Undo,
+        "redo" => Command::Redo,
+        "flip" => Command::Flip,
+        "orientation" if argument.is_empty() => Command::Orientation(None),
+        "orientation" => {
+            Command::Orientation(Some(Orientation::from_name(argument).ok_or_else(|| {
+                format!(
+                    "Unknown Orientation '{}', Type orientation To See The Orientations",
+                    argument
+                )
+            })?))
+        }
+        "theme" if argument.is_empty() => Command::Theme(None),
+        "theme" => Command::Theme(Some(Theme::from_name(argument).ok_or_else(|| {
+            format!("Unknown Theme '{}', Type theme To See The Themes", argument)
+        })?)),
+        "pieces" if argument.is_empty() => Command::Pieces(None),
+        "pieces" => Command::Pieces(Some(Renderer::from_name(argument).ok_or_else(|| {
+            format!(
+                "Unknown Renderer '{}', Type pieces To See The Renderers",
+                argument
+            )
+        })?)),
+        "new" => Command::New(parse_start_position(argument)?),
+        "mode" if argument.is_empty() => Command::Mode(None),
+        "mode" => Command::Mode(Some(VariantKind::from_name(argument).ok_or_else(|| {
+            format!("Unknown Mode '{}', Type mode To See The Modes", argument)
+        })?)),
+        "fen" => Command::Fen,
+        "load" if argument.is_empty() => Err("load Needs A Fen String Or A File Name")?,
+        "load" => Command::Load(argument.to_string()),
+        "save" if argument.is_empty() => Err("save Needs A File Name")?,
+        "save" => Command::Save(argument.to_string()),
+        "diagram" if argument.is_empty() => Err("diagram Needs A File Name")?,
+        "diagram" => parse_diagram(argument)?,
+        "moves" if argument.is_empty() => Command::Moves(None),
+        "moves" => Command::Moves(Some(parse_square(argument)?)),
+        "hint" => Command::Hint,
+        "resign" => Command::Resign,
+        "draw" => Command::Draw,
+        "uci" => Command::Uci,
+        "tui" => Command::Tui,
+        "help" | "?" => Command::Help,
+        "quit" | "exit" => Command::Quit,
+        _ if argument.is_empty() && looks_like_move(keyword) => {
+            Command::Move(parse_chess_move(keyword)?)
+        }
+        _ => Err(format!(
+            "Unknown Command '{}', Type help To See The Commands",
+            input
+        ))?,
+    };
+    Ok(command)
+}
+
+const NEW_USAGE: &str =
+    "new Takes Nothing, A Board Size From 5x5 To 10x10 Or 960 And An Optional Position Number";
+
+/// parse the arguments of new, nothing for standard chess, a board size like 6x6
+/// or 960 and an optional index
+fn parse_start_position(argument: &str) -> MyResult<StartPosition> {
+    let mut arguments = argument.split_whitespace();
+    let start_position = match arguments.next() {
+        None => StartPosition::Standard,
+        Some("960") => match arguments.next() {
+            None => StartPosition::Chess960(None),
+            Some(index) => match index.parse() {
+                Ok(index) if index < chess960::POSITION_COUNT => {
+                    StartPosition::Chess960(Some(index))
+                }
+                _ => Err("The Fischer Random Position Number Is Between 0 And 959")?,
+            },
+        },
+        Some(size) => match BoardSizeInfo::from_name(size) {
+            Some(size) => StartPosition::Sized(size),
+            None => Err(NEW_USAGE)?,
+        },
+    };
+    if arguments.next().is_some() {
+        Err(NEW_USAGE)?;
+    }
+    Ok(start_position)
+}
+
+/// parse the arguments of diagram, the file name followed by squares to highlight and
+/// moves to draw as arrows
+fn parse_diagram(argument: &str) -> MyResult<Command> {
+    let mut arguments = argument.split_whitespace();
+    let file = arguments.next().unwrap_or_default().to_string();
+    let mut highlights = Vec::new();
+    let mut arrows = Vec::new();
+    for mark in arguments {
+        // squares like e4 or a10 are tried before moves
+        match parse_square(mark) {
+            Ok(square) => highlights.push(square),
+            Err(_) => {
+                let chess_move = parse_chess_move(mark)?;
+                arrows.push((chess_move.piece_source, chess_move.piece_dest));
+            }
+        }
+    }
+    Ok(Command::Diagram {
+        file,
+        highlights,
+        arrows,
+    })
+}
+
+/// moves start with a square, so a column letter followed by a row number,
+/// drops start with a piece letter and @
+fn looks_like_move(input: &str) -> bool {
+    let mut chars = input.chars();
+    matches!(
+        (chars.next(), chars.next()),
+        (Some(column), Some(row)) if column.is_ascii_lowercase() && row.is_ascii_digit()
+    ) || matches!(input.chars().nth(1), Some('@'))
+}
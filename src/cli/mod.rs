@@ -0,0 +1,10 @@
+pub mod autosave;
+pub mod batch;
+pub mod clock;
+pub mod command;
+pub mod puzzle;
+pub mod render;
+pub mod session;
+pub mod subcommand;
+pub mod tui;
+pub mod uci;
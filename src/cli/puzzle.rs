@@ -0,0 +1,248 @@
+use std::fs;
+use std::io::{BufRead, Write};
+
+use crate::chess::board_manager::{opposite_color, BoardManager, GameStatus};
+use crate::chess::parse::{position_to_algebraic_notation, ChessMove};
+use crate::chess::pgn::move_to_san;
+use crate::chess::renderer::Orientation;
+
+use super::batch::{find_move, EXIT_SUCCESS, EXIT_USAGE};
+use super::render::{default_theme, render_board};
+use super::subcommand::PuzzleOptions;
+
+/// the puzzles that come with the program, one a line: the position, the moves of the
+/// solution in uci notation and the title, separated by ;
+const BUILTIN_PUZZLES: &str = "\
+6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1;a1a8;Back Rank Mate
+r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4;f3f7;Scholar's Mate
+1r4k1/8/8/8/8/8/5PPP/6K1 b - - 0 1;b8b1;Back Rank Mate For Black
+7k/1R6/5N2/8/8/8/8/6K1 w - - 0 1;b7h7;Arabian Mate
+r6k/6pp/7N/3Q4/8/8/8/6K1 w - - 0 1;d5g8 a8g8 h6f7;Smothered Mate In Two";
+
+/// a position with the moves that solve it, the player plays every other move
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Puzzle {
+    pub fen: String,
+    /// the moves in uci notation, starting with the move of the player
+    pub solution: Vec<String>,
+    pub title: String,
+}
+
+impl Puzzle {
+    /// the number of moves the player makes
+    pub fn player_moves(&self) -> usize {
+        self.solution.len().div_ceil(2)
+    }
+}
+
+/// read the puzzles, one a line as the position, the solution and the title separated
+/// by ;, empty lines and lines starting with # are skipped
+pub fn parse_puzzles(text: &str) -> Result<Vec<Puzzle>, String> {
+    let mut puzzles = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || {
+            format!(
+                "Puzzle Line {} Needs A Position, A Solution And A Title",
+                number + 1
+            )
+        };
+        let mut fields = line.splitn(3, ';').map(str::trim);
+        let (fen, solution, title) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(fen), Some(solution), Some(title)) if !solution.is_empty() => {
+                (fen, solution, title)
+            }
+            _ => return Err(invalid()),
+        };
+        BoardManager::try_from_fen(fen)
+            .map_err(|err| format!("Puzzle Line {} Has A Bad Position, {}", number + 1, err))?;
+        puzzles.push(Puzzle {
+            fen: fen.to_string(),
+            solution: solution.split_whitespace().map(String::from).collect(),
+            title: title.to_string(),
+        });
+    }
+    Ok(puzzles)
+}
+
+/// the puzzles that come with the program
+pub fn builtin_puzzles() -> Vec<Puzzle> {
+    parse_puzzles(BUILTIN_PUZZLES).unwrap()
+}
+
+/// what the player typed while solving a puzzle
+enum Answer {
+    Solved,
+    Skipped,
+    Quit,
+}
+
+/// let the player solve the puzzles one after the other, from the file of the options
+/// or the builtin ones, and tell how many were solved
+pub fn run_puzzles(
+    options: &PuzzleOptions,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> i32 {
+    let first = options.number.unwrap_or(1);
+    let puzzles = match &options.file {
+        Some(file) => fs::read_to_string(file)
+            .map_err(|err| format!("Can't Read The Puzzles {}, {}", file, err))
+            .and_then(|text| parse_puzzles(&text)),
+        None => Ok(builtin_puzzles()),
+    };
+    let puzzles = match puzzles {
+        Ok(puzzles) if first >= 1 && first <= puzzles.len() => puzzles,
+        Ok(puzzles) => {
+            eprintln!(
+                "Error: The Puzzle Number Is Between 1 And {}",
+                puzzles.len()
+            );
+            return EXIT_USAGE;
+        }
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return EXIT_USAGE;
+        }
+    };
+
+    let mut solved = 0;
+    let mut tried = 0;
+    for (index, puzzle) in puzzles.iter().enumerate().skip(first - 1) {
+        let _ = writeln!(
+            output,
+            "Puzzle {} Of {}: {}",
+            index + 1,
+            puzzles.len(),
+            puzzle.title
+        );
+        tried += 1;
+        match solve_puzzle(puzzle, input, output) {
+            Answer::Solved => solved += 1,
+            Answer::Skipped => {}
+            Answer::Quit => break,
+        }
+    }
+    let _ = writeln!(output, "Solved {} Of {} Puzzles", solved, tried);
+    EXIT_SUCCESS
+}
+
+/// play the puzzle until it is solved, given up or the input ends
+fn solve_puzzle(puzzle: &Puzzle, input: &mut impl BufRead, output: &mut impl Write) -> Answer {
+    let mut board = BoardManager::new_from_fen(&puzzle.fen);
+    let player = board.side_to_move();
+    let theme = default_theme();
+    let _ = writeln!(
+        output,
+        "{}\n{:?} To Move, Find The Mate In {}",
+        render_board(&board, theme, Orientation::SideToMove),
+        player,
+        puzzle.player_moves()
+    );
+
+    let mut step = 0;
+    loop {
+        let _ = write!(output, "puzzle > ");
+        let _ = output.flush();
+        let mut line = String::new();
+        if !matches!(input.read_line(&mut line), Ok(read) if read > 0) {
+            return Answer::Quit;
+        }
+        let text = line.trim();
+        let expected = match find_legal_move(&mut board, &puzzle.solution[step]) {
+            Some(chess_move) => chess_move,
+            None => {
+                let _ = writeln!(
+                    output,
+                    "The Solution Of The Puzzle Is Not Legal, Skipping It"
+                );
+                return Answer::Skipped;
+            }
+        };
+        match text {
+            "" => continue,
+            "quit" => return Answer::Quit,
+            "skip" => return Answer::Skipped,
+            "hint" => {
+                let square = position_to_algebraic_notation(&expected.piece_source);
+                let _ = writeln!(output, "Hint: The Move Starts On {}", square);
+                continue;
+            }
+            "solution" => {
+                let mut moves = Vec::new();
+                for uci in &puzzle.solution[step..] {
+                    match find_legal_move(&mut board, uci) {
+                        Some(chess_move) => {
+                            moves.push(move_to_san(&mut board, &chess_move));
+                            let _ = board.handle_move(&chess_move);
+                        }
+                        None => break,
+                    }
+                }
+                let _ = writeln!(output, "Solution: {}", moves.join(" "));
+                return Answer::Skipped;
+            }
+            _ => {}
+        }
+
+        let chess_move = match find_legal_move(&mut board, text) {
+            Some(chess_move) => chess_move,
+            None => {
+                let _ = writeln!(
+                    output,
+                    "'{}' Is Not A Legal Move, Type hint, solution, skip Or quit",
+                    text
+                );
+                continue;
+            }
+        };
+        let san = move_to_san(&mut board, &chess_move);
+        let mut after = board.clone();
+        let _ = after.handle_move(&chess_move);
+        let mates = matches!(after.game_status(), GameStatus::Checkmate { .. });
+        if board.move_to_uci(&chess_move) != board.move_to_uci(&expected) && !mates {
+            let _ = writeln!(output, "{} Is Not The Move, Try Again", san);
+            continue;
+        }
+        board = after;
+        step += 1;
+        if mates || step >= puzzle.solution.len() {
+            let _ = writeln!(output, "{} Solves It", san);
+            return Answer::Solved;
+        }
+
+        let reply = match find_legal_move(&mut board, &puzzle.solution[step]) {
+            Some(reply) => reply,
+            None => {
+                let _ = writeln!(
+                    output,
+                    "The Solution Of The Puzzle Is Not Legal, Skipping It"
+                );
+                return Answer::Skipped;
+            }
+        };
+        let reply_san = move_to_san(&mut board, &reply);
+        let _ = board.handle_move(&reply);
+        step += 1;
+        let _ = writeln!(
+            output,
+            "{} Is Right, {:?} Replies {}\n{}",
+            san,
+            opposite_color(player),
+            reply_san,
+            render_board(&board, theme, Orientation::SideToMove)
+        );
+    }
+}
+
+/// the legal move written in uci or in standard algebraic notation
+fn find_legal_move(board: &mut BoardManager, text: &str) -> Option<ChessMove> {
+    let uci = board.move_to_uci(&find_move(board, text)?);
+    let legal_moves = board.legal_moves();
+    legal_moves
+        .into_iter()
+        .find(|chess_move| board.move_to_uci(chess_move) == uci)
+}
@@ -0,0 +1,247 @@
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal};
+
+use crate::chess::board_manager::BoardManager;
+use crate::chess::piece::{Color, Position};
+use crate::chess::renderer::{Orientation, Renderer};
+
+/// the color schemes of the board
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Theme {
+    /// the text board without colors
+    Plain,
+    Classic,
+    HighContrast,
+    /// colors told apart with any kind of color blindness, from the Okabe-Ito palette
+    ColorblindSafe,
+    /// terminal attributes only, reverse video for the dark squares
+    Monochrome,
+}
+
+pub const THEMES: [Theme; 5] = [
+    Theme::Plain,
+    Theme::Classic,
+    Theme::HighContrast,
+    Theme::ColorblindSafe,
+    Theme::Monochrome,
+];
+
+/// the sgr parameters of every part of the board, an empty string keeps the terminal default
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Palette {
+    pub light_square: &'static str,
+    pub dark_square: &'static str,
+    pub last_move: &'static str,
+    pub check: &'static str,
+    pub cursor: &'static str,
+    pub selected: &'static str,
+    pub target: &'static str,
+    pub popup: &'static str,
+    pub white_piece: &'static str,
+    pub black_piece: &'static str,
+}
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Plain => "plain",
+            Theme::Classic => "classic",
+            Theme::HighContrast => "high-contrast",
+            Theme::ColorblindSafe => "colorblind",
+            Theme::Monochrome => "monochrome",
+        }
+    }
+
+    /// the theme by its name, case insensitive
+    pub fn from_name(name: &str) -> Option<Theme> {
+        let name = name.to_lowercase();
+        THEMES.iter().copied().find(|theme| theme.name() == name)
+    }
+
+    /// the colors of the theme, the plain board has none so it gets the monochrome attributes
+    pub fn palette(&self) -> Palette {
+        match self {
+            Theme::Classic => Palette {
+                light_square: "48;5;180",
+                dark_square: "48;5;137",
+                last_move: "48;5;143",
+                check: "48;5;160",
+                cursor: "48;5;33",
+                selected: "48;5;28",
+                target: "48;5;107",
+                popup: "48;5;231",
+                white_piece: "38;5;231",
+                black_piece: "38;5;16",
+            },
+            Theme::HighContrast => Palette {
+                light_square: "48;5;231",
+                dark_square: "48;5;244",
+                last_move: "48;5;226",
+                check: "48;5;196",
+                cursor: "48;5;201",
+                selected: "48;5;46",
+                target: "48;5;51",
+                popup: "48;5;231",
+                white_piece: "1;38;5;21",
+                black_piece: "1;38;5;16",
+            },
+            Theme::ColorblindSafe => Palette {
+                light_square: "48;5;254",
+                dark_square: "48;5;74",
+                last_move: "48;5;227",
+                check: "48;5;166",
+                cursor: "48;5;25",
+                selected: "48;5;178",
+                target: "48;5;175",
+                popup: "48;5;254",
+                white_piece: "38;5;231",
+                black_piece: "38;5;16",
+            },
+            Theme::Plain | Theme::Monochrome => Palette {
+                light_square: "",
+                dark_square: "7",
+                last_move: "4",
+                check: "1;5",
+                cursor: "1;4;7",
+                selected: "1",
+                target: "4",
+                popup: "1",
+                white_piece: "1",
+                black_piece: "",
+            },
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// the theme to start with, the plain board when NO_COLOR is set or the output
+/// isn't a terminal, as https://no-color.org asks
+pub fn default_theme() -> Theme {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if no_color || !io::stdout().is_terminal() {
+        Theme::Plain
+    } else {
+        Theme::Classic
+    }
+}
+
+/// the escape sequence that sets the parameters, the empty ones skipped
+pub fn sgr(parameters: &[&str]) -> String {
+    let parameters: Vec<&str> = parameters
+        .iter()
+        .copied()
+        .filter(|parameter| !parameter.is_empty())
+        .collect();
+    format!("\x1b[{}m", parameters.join(";"))
+}
+
+/// the escape sequence that resets the colors and attributes
+pub const RESET: &str = "\x1b[0m";
+
+/// the sgr parameters of the piece color
+pub fn piece_foreground(palette: &Palette, color: Color) -> &'static str {
+    match color {
+        Color::White => palette.white_piece,
+        Color::Black => palette.black_piece,
+    }
+}
+
+/// the pieces the color captured followed by its material lead, like ♟♟♞ +4,
+/// empty when the color captured nothing and isn't ahead
+pub fn captures_text(board: &BoardManager, captor: Color) -> String {
+    let mut text: String = board
+        .captured(captor)
+        .iter()
+        .map(|piece| board.renderer().piece_char(piece))
+        .collect();
+    if let Some((leader, points)) = board.material().advantage() {
+        if leader == captor {
+            text.push_str(&format!(" +{}", points));
+        }
+    }
+    text.trim_start().to_string()
+}
+
+/// the board in the colors of the theme, the plain theme is the text board
+pub fn render_board(board: &BoardManager, theme: Theme, orientation: Orientation) -> String {
+    if theme == Theme::Plain {
+        return board.renderer().render(board, orientation);
+    }
+    board_lines(board, &theme.palette(), orientation, |_| None).join("\n")
+}
+
+/// the lines of the colored board, the column letters above and below and the row numbers
+/// at both sides, every square three cells wide. the highlight gives the background of the
+/// squares that have a special one
+pub fn board_lines(
+    board: &BoardManager,
+    palette: &Palette,
+    orientation: Orientation,
+    highlight: impl Fn(&Position) -> Option<&'static str>,
+) -> Vec<String> {
+    let size = board.size();
+    let letters: String = (b'a'..b'a' + size.column_count() as u8)
+        .map(|letter| format!(" {} ", letter as char))
+        .collect();
+    let letters = format!("   {}   ", letters);
+    let rows: Vec<i8> = if orientation.bottom(board) == Color::White {
+        (0..size.row_count() as i8).rev().collect()
+    } else {
+        (0..size.row_count() as i8).collect()
+    };
+
+    let side_to_move = board.side_to_move();
+    let checked_king = (board.has_king(side_to_move) && board.is_check(side_to_move))
+        .then(|| board.king_position(side_to_move));
+    let last_move = board.history().back().map(|turn| turn.chess_move);
+    // the check and the last move are drawn over the square color
+    let background = |position: &Position| {
+        if checked_king == Some(*position) {
+            palette.check
+        } else if last_move.is_some_and(|chess_move| {
+            chess_move.piece_dest == *position
+                || (chess_move.dropped.is_none() && chess_move.piece_source == *position)
+        }) {
+            palette.last_move
+        } else if (position.x + position.y) % 2 == 1 {
+            palette.light_square
+        } else {
+            palette.dark_square
+        }
+    };
+
+    let mut lines = vec![letters.clone()];
+    for row in rows {
+        let mut line = format!("{:>2} ", row + 1);
+        for column in 0..size.column_count() as i8 {
+            let position = Position::new(row, column);
+            let background = highlight(&position).unwrap_or_else(|| background(&position));
+            let square = match board.piece_at(&position) {
+                // a wide glyph fills the cell after it
+                Some(piece) if board.renderer() == Renderer::WideGlyphs => format!(
+                    "{} {}",
+                    sgr(&[background, piece_foreground(palette, piece.p_color)]),
+                    piece
+                ),
+                Some(piece) => format!(
+                    "{} {} ",
+                    sgr(&[background, piece_foreground(palette, piece.p_color)]),
+                    board.renderer().piece_char(&piece)
+                ),
+                None => format!("{}   ", sgr(&[background])),
+            };
+            line.push_str(&square);
+            line.push_str(RESET);
+        }
+        line.push_str(&format!(" {:<2}", row + 1));
+        lines.push(line);
+    }
+    lines.push(letters);
+    lines
+}
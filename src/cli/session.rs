@@ -0,0 +1,681 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::chess::board_manager::{opposite_color, BoardManager, GameStatus};
+use crate::chess::chess960;
+use crate::chess::engine;
+use crate::chess::parse::{position_to_algebraic_notation, ChessMove};
+use crate::chess::pgn::{parse_pgn, write_pgn};
+use crate::chess::piece::{Color, Piece};
+use crate::chess::renderer::{Orientation, Renderer, ORIENTATIONS, RENDERERS};
+use crate::chess::svg::{render_svg, DiagramOptions};
+use crate::chess::variant::{Variant, CHECKS_TO_WIN, VARIANTS};
+
+use super::clock::{Clocks, TimeControl};
+use super::command::{Command, StartPosition, HELP_TEXT};
+use super::render::{captures_text, render_board, Theme, THEMES};
+
+type MyResult<T> = Result<T, Box<dyn Error>>;
+
+/// how many moves ahead the hint looks
+const HINT_DEPTH: u8 = 2;
+
+/// the way the game ended
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum GameResult {
+    Checkmate { winner: Color },
+    Stalemate,
+    Resignation { winner: Color },
+    OutOfTime { winner: Color },
+    DrawAgreed,
+    ThirdCheck { winner: Color },
+    OutOfMoves { winner: Color },
+    KingOfTheHill { winner: Color },
+    KingExploded { winner: Color },
+    AllPiecesCaptured { winner: Color },
+    GoalReached { winner: Color },
+    RaceDrawn,
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameResult::Checkmate { winner } => write!(f, "Checkmate, {:?} Wins", winner),
+            GameResult::Stalemate => write!(f, "Stalemate, The Game Is A Draw"),
+            GameResult::Resignation { winner } => {
+                write!(
+                    f,
+                    "{:?} Resigned, {:?} Wins",
+                    opposite_color(*winner),
+                    winner
+                )
+            }
+            GameResult::OutOfTime { winner } => {
+                write!(
+                    f,
+                    "{:?} Ran Out Of Time, {:?} Wins",
+                    opposite_color(*winner),
+                    winner
+                )
+            }
+            GameResult::DrawAgreed => write!(f, "Draw Agreed"),
+            GameResult::ThirdCheck { winner } => write!(f, "Third Check, {:?} Wins", winner),
+            GameResult::OutOfMoves { winner } => {
+                write!(
+                    f,
+                    "{:?} Has No Pieces Or Moves Left, {:?} Wins",
+                    winner, winner
+                )
+            }
+            GameResult::KingOfTheHill { winner } => {
+                write!(f, "{:?} King Reached The Hill, {:?} Wins", winner, winner)
+            }
+            GameResult::KingExploded { winner } => write!(
+                f,
+                "{:?} King Exploded, {:?} Wins",
+                opposite_color(*winner),
+                winner
+            ),
+            GameResult::AllPiecesCaptured { winner } => write!(
+                f,
+                "Every {:?} Piece Was Captured, {:?} Wins",
+                opposite_color(*winner),
+                winner
+            ),
+            GameResult::GoalReached { winner } => {
+                write!(
+                    f,
+                    "{:?} King Reached The Last Row, {:?} Wins",
+                    winner, winner
+                )
+            }
+            GameResult::RaceDrawn => {
+                write!(f, "Both Kings Reached The Last Row, The Game Is A Draw")
+            }
+        }
+    }
+}
+
+impl GameResult {
+    /// the color that won, None when the game is a draw
+    pub fn winner(&self) -> Option<Color> {
+        match *self {
+            GameResult::Checkmate { winner }
+            | GameResult::Resignation { winner }
+            | GameResult::OutOfTime { winner }
+            | GameResult::ThirdCheck { winner }
+            | GameResult::OutOfMoves { winner }
+            | GameResult::KingOfTheHill { winner }
+            | GameResult::KingExploded { winner }
+            | GameResult::AllPiecesCaptured { winner }
+            | GameResult::GoalReached { winner } => Some(winner),
+            GameResult::Stalemate | GameResult::DrawAgreed | GameResult::RaceDrawn => None,
+        }
+    }
+
+    /// the result as written at the end of a pgn game
+    pub fn pgn_result(&self) -> &'static str {
+        match self.winner() {
+            Some(Color::White) => "1-0",
+            Some(Color::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+/// an interactive game, the board and everything the players did with it
+pub struct GameSession {
+    board: BoardManager,
+    result: Option<GameResult>,
+    draw_offer: Option<Color>,
+    theme: Theme,
+    clocks: Clocks,
+}
+
+impl Default for GameSession {
+    fn default() -> Self {
+        GameSession::from_board(BoardManager::new())
+    }
+}
+
+impl GameSession {
+    pub fn new() -> Self {
+        GameSession::default()
+    }
+
+    /// start a session from an existing position
+    pub fn from_board(board: BoardManager) -> Self {
+        let mut session = GameSession {
+            board,
+            result: None,
+            draw_offer: None,
+            theme: Theme::Plain,
+            clocks: Clocks::new(),
+        };
+        session.update_result();
+        session
+    }
+
+    pub fn board(&self) -> &BoardManager {
+        &self.board
+    }
+
+    /// the colors the board is shown in
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// change the settings that are given, the others are kept
+    pub fn apply_settings(
+        &mut self,
+        theme: Option<Theme>,
+        renderer: Option<Renderer>,
+        orientation: Option<Orientation>,
+    ) {
+        if let Some(theme) = theme {
+            self.theme = theme;
+        }
+        if let Some(renderer) = renderer {
+            self.board.set_renderer(renderer);
+        }
+        if let Some(orientation) = orientation {
+            self.board.set_orientation(orientation);
+        }
+    }
+
+    /// the board in the colors of the theme
+    pub fn render_board(&self) -> String {
+        render_board(&self.board, self.theme, self.board.orientation())
+    }
+
+    /// the time each player spent on the game
+    pub fn clocks(&self) -> &Clocks {
+        &self.clocks
+    }
+
+    /// play under the time control, the moves already made get their increments
+    pub fn set_time_control(&mut self, time_control: TimeControl) {
+        let side_to_move = self.board.side_to_move();
+        let plies = self.board.history().len() as u32;
+        // the side to move made the fewer moves when the counts differ
+        let (to_move_moves, other_moves) = (plies / 2, plies - plies / 2);
+        let (white_moves, black_moves) = match side_to_move {
+            Color::White => (to_move_moves, other_moves),
+            Color::Black => (other_moves, to_move_moves),
+        };
+        self.clocks
+            .set_time_control(time_control, white_moves, black_moves);
+    }
+
+    /// the result of the game if it ended
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// the game as pgn, the time the players spent and the board settings are extra tags
+    pub fn to_pgn(&self) -> String {
+        let side_to_move = self.board.side_to_move();
+        let seconds = |color| format!("{:.3}", self.clocks.used(color, side_to_move).as_secs_f64());
+        let tags = [
+            ("WhiteTimeUsed", seconds(Color::White)),
+            ("BlackTimeUsed", seconds(Color::Black)),
+            ("Theme", self.theme.name().to_string()),
+            ("Pieces", self.board.renderer().name().to_string()),
+            ("Orientation", self.board.orientation().to_string()),
+        ];
+        let time_control = self
+            .clocks
+            .time_control()
+            .map(|time_control| ("TimeControl", time_control.to_pgn_tag()));
+        let tags: Vec<(&str, String)> = tags.into_iter().chain(time_control).collect();
+        let result = self.result.map_or("*", |result| result.pgn_result());
+        write_pgn(&self.board, &tags, result)
+    }
+
+    /// restore the game written by to_pgn, the moves are played again so they can be
+    /// taken back, a result the position doesn't explain is taken as a resignation or
+    /// an agreed draw
+    pub fn from_pgn(text: &str) -> MyResult<GameSession> {
+        let game = parse_pgn(text)?;
+        let mut board = game.board()?;
+        if let Some(renderer) = game.tag("Pieces").and_then(Renderer::from_name) {
+            board.set_renderer(renderer);
+        }
+        if let Some(orientation) = game.tag("Orientation").and_then(Orientation::from_name) {
+            board.set_orientation(orientation);
+        }
+
+        let mut session = GameSession::from_board(board);
+        if let Some(theme) = game.tag("Theme").and_then(Theme::from_name) {
+            session.theme = theme;
+        }
+        let used = |name| {
+            game.tag(name)
+                .and_then(|seconds| seconds.parse().ok())
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                .unwrap_or_default()
+        };
+        session.clocks = Clocks::with_used(used("WhiteTimeUsed"), used("BlackTimeUsed"));
+        if let Some(time_control) = game.tag("TimeControl").and_then(TimeControl::from_pgn_tag) {
+            session.set_time_control(time_control);
+        }
+        session.update_result();
+        if session.result.is_none() {
+            let side_to_move = session.board.side_to_move();
+            let lost = |winner| {
+                if session
+                    .clocks
+                    .is_out_of_time(opposite_color(winner), side_to_move)
+                {
+                    GameResult::OutOfTime { winner }
+                } else {
+                    GameResult::Resignation { winner }
+                }
+            };
+            session.result = match game.result.as_str() {
+                "1-0" => Some(lost(Color::White)),
+                "0-1" => Some(lost(Color::Black)),
+                "1/2-1/2" => Some(GameResult::DrawAgreed),
+                _ => None,
+            };
+        }
+        Ok(session)
+    }
+
+    /// run a command and return the text to show to the players
+    pub fn execute(&mut self, command: Command) -> MyResult<String> {
+        match command {
+            Command::Move(chess_move) => self.play_move(&chess_move),
+            Command::Undo => self.undo(),
+            Command::Redo => self.redo(),
+            Command::Flip => {
+                self.board.flip();
+                Ok(self.board_report())
+            }
+            Command::Orientation(None) => Ok(self.orientation_menu()),
+            Command::Orientation(Some(orientation)) => {
+                self.board.set_orientation(orientation);
+                Ok(format!(
+                    "Board Shown From {}\n{}",
+                    orientation,
+                    self.board_report()
+                ))
+            }
+            Command::Theme(None) => Ok(self.theme_menu()),
+            Command::Theme(Some(theme)) => {
+                self.theme = theme;
+                Ok(format!("Theme {}\n{}", theme, self.board_report()))
+            }
+            Command::Pieces(None) => Ok(self.renderer_menu()),
+            Command::Pieces(Some(renderer)) => {
+                self.board.set_renderer(renderer);
+                Ok(format!(
+                    "Pieces Drawn As {}\n{}",
+                    renderer,
+                    self.board_report()
+                ))
+            }
+            Command::New(start_position) => {
+                let variant = Rc::clone(self.board.variant());
+                let board = match start_position {
+                    StartPosition::Standard => BoardManager::new_variant(variant),
+                    StartPosition::Chess960(_) | StartPosition::Sized(_)
+                        if !variant.has_standard_start() =>
+                    {
+                        return Err(
+                            format!("{} Has Its Own Start Position", variant.title()).into()
+                        );
+                    }
+                    StartPosition::Chess960(index) => {
+                        let index = index.unwrap_or_else(chess960::random_index);
+                        let mut board = BoardManager::new_chess960(index)
+                            .ok_or("The Fischer Random Position Number Is Between 0 And 959")?;
+                        board.set_variant(variant);
+                        board
+                    }
+                    StartPosition::Sized(size) => {
+                        let mut board = BoardManager::new_sized(size);
+                        board.set_variant(variant);
+                        board
+                    }
+                };
+                self.restart(board);
+                Ok(self.board_report())
+            }
+            Command::Mode(None) => Ok(self.mode_menu()),
+            Command::Mode(Some(variant)) => {
+                self.restart(BoardManager::new_variant(variant));
+                Ok(format!("Playing {}\n{}", variant, self.board_report()))
+            }
+            Command::Fen => Ok(self.board.to_fen()),
+            Command::Load(fen_or_file) => {
+                let fen_string = if Path::new(&fen_or_file).is_file() {
+                    fs::read_to_string(&fen_or_file)?
+                } else {
+                    fen_or_file
+                };
+                let variant = Rc::clone(self.board.variant());
+                let board = BoardManager::try_from_variant_fen(fen_string.trim(), variant)?;
+                self.restart(board);
+                Ok(self.board_report())
+            }
+            Command::Save(file_name) => {
+                fs::write(&file_name, format!("{}\n", self.board.to_fen()))?;
+                Ok(format!("Saved The Position To {}", file_name))
+            }
+            Command::Diagram {
+                file,
+                mut highlights,
+                arrows,
+            } => {
+                if let Some(turn) = self.board.history().back() {
+                    highlights.push(turn.chess_move.piece_dest);
+                    if turn.chess_move.dropped.is_none() {
+                        highlights.push(turn.chess_move.piece_source);
+                    }
+                }
+                let options = DiagramOptions {
+                    orientation: self.board.orientation(),
+                    highlights,
+                    arrows,
+                    ..DiagramOptions::default()
+                };
+                fs::write(&file, render_svg(&self.board, &options))?;
+                Ok(format!("Saved The Diagram To {}", file))
+            }
+            Command::Moves(square) => {
+                let legal_moves = match square {
+                    Some(position) => self.board.legal_moves_from(&position),
+                    None => self.board.legal_moves(),
+                };
+                if legal_moves.is_empty() {
+                    return Ok("No Legal Moves".to_string());
+                }
+                let legal_moves: Vec<String> = legal_moves
+                    .iter()
+                    .map(|m| self.board.move_to_uci(m))
+                    .collect();
+                Ok(legal_moves.join(" "))
+            }
+            Command::Hint => {
+                self.ensure_game_not_over()?;
+                let mut board = self.board.clone();
+                match engine::best_move(&mut board, HINT_DEPTH) {
+                    Some(chess_move) => {
+                        Ok(format!("Hint: {}", self.board.move_to_uci(&chess_move)))
+                    }
+                    None => Ok("No Legal Moves".to_string()),
+                }
+            }
+            Command::Resign => {
+                self.ensure_game_not_over()?;
+                let result = GameResult::Resignation {
+                    winner: opposite_color(self.board.side_to_move()),
+                };
+                self.result = Some(result);
+                Ok(result.to_string())
+            }
+            Command::Draw => self.offer_draw(),
+            Command::Uci => Err("uci Is Handled By The Game Loop")?,
+            Command::Tui => Err("tui Is Handled By The Game Loop")?,
+            Command::Help => Ok(HELP_TEXT.to_string()),
+            Command::Quit => Ok("Goodbye".to_string()),
+        }
+    }
+
+    fn play_move(&mut self, chess_move: &ChessMove) -> MyResult<String> {
+        self.ensure_game_not_over()?;
+        let mover = self.board.side_to_move();
+        // the flag is seen when the player tries to move
+        if self.clocks.is_out_of_time(mover, mover) {
+            let result = GameResult::OutOfTime {
+                winner: opposite_color(mover),
+            };
+            self.result = Some(result);
+            return Ok(result.to_string());
+        }
+        self.board.handle_move(chess_move)?;
+        self.clocks.end_turn(mover);
+
+        if self
+            .draw_offer
+            .is_some_and(|offered_by| offered_by != mover)
+        {
+            self.draw_offer = None;
+        }
+        self.update_result();
+        Ok(self.board_report())
+    }
+
+    fn undo(&mut self) -> MyResult<String> {
+        let chess_turn = self.board.undo()?;
+        self.draw_offer = None;
+        self.update_result();
+        Ok(format!(
+            "Took Back {}\n{}",
+            chess_turn.chess_move,
+            self.board_report()
+        ))
+    }
+
+    fn redo(&mut self) -> MyResult<String> {
+        let chess_turn = self.board.redo()?;
+        self.update_result();
+        Ok(format!(
+            "Played Again {}\n{}",
+            chess_turn.chess_move,
+            self.board_report()
+        ))
+    }
+
+    fn offer_draw(&mut self) -> MyResult<String> {
+        self.ensure_game_not_over()?;
+        let side_to_move = self.board.side_to_move();
+        match self.draw_offer {
+            Some(offered_by) if offered_by != side_to_move => {
+                self.result = Some(GameResult::DrawAgreed);
+                Ok(GameResult::DrawAgreed.to_string())
+            }
+            _ => {
+                self.draw_offer = Some(side_to_move);
+                Ok(format!(
+                    "{:?} Offers A Draw, {:?} Can Type draw To Accept",
+                    side_to_move,
+                    opposite_color(side_to_move)
+                ))
+            }
+        }
+    }
+
+    fn restart(&mut self, mut board: BoardManager) {
+        board.set_orientation(self.board.orientation());
+        board.set_renderer(self.board.renderer());
+        let theme = self.theme;
+        let time_control = self.clocks.time_control();
+        *self = GameSession::from_board(board);
+        self.theme = theme;
+        if let Some(time_control) = time_control {
+            self.set_time_control(time_control);
+        }
+    }
+
+    fn ensure_game_not_over(&self) -> MyResult<()> {
+        if let Some(result) = self.result {
+            Err(format!(
+                "The Game Is Over ({}), Type new To Start Again Or undo To Go Back",
+                result
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn update_result(&mut self) {
+        self.result = match self.board.game_status() {
+            GameStatus::Checkmate { winner } => Some(GameResult::Checkmate { winner }),
+            GameStatus::Stalemate => Some(GameResult::Stalemate),
+            GameStatus::ThirdCheck { winner } => Some(GameResult::ThirdCheck { winner }),
+            GameStatus::OutOfMoves { winner } => Some(GameResult::OutOfMoves { winner }),
+            GameStatus::KingOfTheHill { winner } => Some(GameResult::KingOfTheHill { winner }),
+            GameStatus::KingExploded { winner } => Some(GameResult::KingExploded { winner }),
+            GameStatus::AllPiecesCaptured { winner } => {
+                Some(GameResult::AllPiecesCaptured { winner })
+            }
+            GameStatus::GoalReached { winner } => Some(GameResult::GoalReached { winner }),
+            GameStatus::RaceDrawn => Some(GameResult::RaceDrawn),
+            GameStatus::Ongoing => None,
+        };
+    }
+
+    /// the modes that can be played, the current one marked
+    fn mode_menu(&self) -> String {
+        let mut menu = String::from("modes:");
+        for (number, variant) in VARIANTS.iter().enumerate() {
+            let marker = if variant.name() == self.board.variant().name() {
+                '*'
+            } else {
+                ' '
+            };
+            menu.push_str(&format!(
+                "\n {}{}. {:<12} {}",
+                marker,
+                number + 1,
+                variant.name(),
+                variant.description()
+            ));
+        }
+        menu.push_str("\nType mode <name> Or mode <number> To Start A New Game In The Mode");
+        menu
+    }
+
+    /// the themes of the board, the current one marked
+    fn theme_menu(&self) -> String {
+        let mut menu = String::from("themes:");
+        for theme in THEMES {
+            let marker = if theme == self.theme { '*' } else { ' ' };
+            menu.push_str(&format!("\n {}{}", marker, theme));
+        }
+        menu.push_str("\nType theme <name> To Switch, plain Shows The Board Without Colors");
+        menu
+    }
+
+    /// the ways to turn the board, the current one marked
+    fn orientation_menu(&self) -> String {
+        let mut menu = String::from("orientations:");
+        for orientation in ORIENTATIONS {
+            let marker = if orientation.name() == self.board.orientation().name() {
+                '*'
+            } else {
+                ' '
+            };
+            menu.push_str(&format!("\n {}{}", marker, orientation));
+        }
+        menu.push_str(
+            "\nType orientation <name> To Switch, player Can Be Followed By white Or black",
+        );
+        menu
+    }
+
+    /// the ways to draw the pieces, the current one marked
+    fn renderer_menu(&self) -> String {
+        let mut menu = String::from("pieces:");
+        for renderer in RENDERERS {
+            let marker = if renderer == self.board.renderer() {
+                '*'
+            } else {
+                ' '
+            };
+            menu.push_str(&format!("\n {}{}", marker, renderer));
+        }
+        menu.push_str("\nType pieces <name> To Switch");
+        menu
+    }
+
+    /// the state of the game, the result or the side to move and who gives it check
+    pub fn status(&self) -> String {
+        let side_to_move = self.board.side_to_move();
+        match self.result {
+            Some(result) => result.to_string(),
+            None if self.board.is_check(side_to_move) => {
+                let checkers: Vec<String> = self
+                    .board
+                    .checkers()
+                    .iter()
+                    .map(position_to_algebraic_notation)
+                    .collect();
+                format!(
+                    "{:?} To Move, Check From {}",
+                    side_to_move,
+                    checkers.join(" And ")
+                )
+            }
+            None => format!("{:?} To Move", side_to_move),
+        }
+    }
+
+    /// the board with the pieces each color captured beside the column letters on its side
+    fn board_with_captures(&self) -> String {
+        let mut lines: Vec<String> = self.render_board().lines().map(String::from).collect();
+        let bottom = self.board.orientation().bottom(&self.board);
+        let last_line = lines.len() - 1;
+        for (line, color) in [(0, opposite_color(bottom)), (last_line, bottom)] {
+            let captures = captures_text(&self.board, color);
+            if !captures.is_empty() {
+                lines[line] = format!("{}   {}", lines[line].trim_end(), captures);
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// the board followed by the state of the game
+    fn board_report(&self) -> String {
+        let state = self.status();
+        let variant = self.board.variant();
+        if variant.counts_checks() {
+            let checks_given = self.board.checks_given();
+            format!(
+                "{}\nChecks Given: White {}/{}, Black {}/{}\n{}",
+                self.board_with_captures(),
+                checks_given.white,
+                CHECKS_TO_WIN,
+                checks_given.black,
+                CHECKS_TO_WIN,
+                state
+            )
+        } else if variant.has_pockets() {
+            let pockets = self.board.pockets();
+            let pocket_text = |color| {
+                let pieces: Vec<String> = pockets
+                    .of(color)
+                    .pieces()
+                    .iter()
+                    .map(|p_type| {
+                        let piece = Piece::new(*p_type, color);
+                        self.board.renderer().piece_char(&piece).to_string()
+                    })
+                    .collect();
+                if pieces.is_empty() {
+                    "-".to_string()
+                } else {
+                    pieces.join(" ")
+                }
+            };
+            format!(
+                "{}\nPockets: White {}, Black {}\n{}",
+                self.board_with_captures(),
+                pocket_text(Color::White),
+                pocket_text(Color::Black),
+                state
+            )
+        } else {
+            format!("{}\n{}", self.board_with_captures(), state)
+        }
+    }
+}
@@ -0,0 +1,526 @@
+use std::fs;
+use std::io::{BufRead, Write};
+use std::time::{Duration, Instant};
+
+use crate::chess::board_manager::BoardManager;
+use crate::chess::engine;
+use crate::chess::error::PgnError;
+use crate::chess::perft::{divide, perft};
+use crate::chess::pgn::{move_to_san, parse_pgn, write_pgn};
+use crate::chess::piece::Color;
+use crate::chess::variant::VariantKind;
+use crate::{parse_game_options, GameOptions, PLAY_USAGE, USAGE};
+
+use super::batch::{play_moves, start_board, EXIT_ILLEGAL_MOVE, EXIT_SUCCESS, EXIT_USAGE};
+use super::session::GameSession;
+
+pub const UCI_USAGE: &str = "usage: cte uci
+talk to a chess program over the universal chess interface on the standard input and output";
+
+pub const PERFT_USAGE: &str = "usage: cte perft <depth> [--fen <fen>] [--mode <variant>] [--divide]
+count the positions reached after depth moves, --divide counts them after each first move";
+
+pub const ANALYZE_USAGE: &str = "usage: cte analyze [--fen <fen>] [--moves <e2e4,e7e5,...>] \
+[--mode <variant>] [--depth <n>]
+search the best move of the position and score it for white, the depth is 3 when not given";
+
+pub const PGN_USAGE: &str = "usage: cte pgn <file|-> [--to <pgn|fen|uci|san>] [--validate]
+play the first game of the pgn file again and write it in the format, pgn when not given,
+--validate only tells whether every move is legal and exits with 1 when one is not";
+
+pub const PUZZLE_USAGE: &str = "usage: cte puzzle [--file <file>] [--number <n>]
+solve checkmate puzzles, type hint, solution, skip or quit instead of a move,
+the file has a puzzle a line: the fen, the solution in uci and the title separated by ;";
+
+pub const BENCH_USAGE: &str = "usage: cte bench [--depth <n>]
+time perft on a few known positions and a search of the start position, the depth is 3 when not given";
+
+/// how many moves ahead analyze and bench look when the depth isn't given
+const DEFAULT_DEPTH: u8 = 3;
+
+/// the deepest search the commands accept, deeper ones would not end in reasonable time
+const MAX_DEPTH: u8 = 10;
+
+/// the positions bench runs perft on, with the name it shows
+const BENCH_POSITIONS: [(&str, &str); 3] = [
+    (
+        "start",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    ),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    ),
+    ("endgame", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
+];
+
+/// the command the program was started with
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Subcommand {
+    Play(GameOptions),
+    Uci,
+    Perft(PerftOptions),
+    Analyze(AnalyzeOptions),
+    Pgn(PgnOptions),
+    Puzzle(PuzzleOptions),
+    Bench(BenchOptions),
+    /// print the usage of a command and exit
+    Help(&'static str),
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct PerftOptions {
+    pub depth: u8,
+    pub fen: Option<String>,
+    pub mode: Option<VariantKind>,
+    /// count the positions after each first move too
+    pub divide: bool,
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct AnalyzeOptions {
+    pub fen: Option<String>,
+    /// the moves played before the position is analyzed
+    pub moves: Vec<String>,
+    pub mode: Option<VariantKind>,
+    pub depth: Option<u8>,
+}
+
+/// what the pgn command writes
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub enum PgnFormat {
+    /// the game written again with the moves in standard algebraic notation
+    #[default]
+    Pgn,
+    /// the position at the end of the game
+    Fen,
+    Uci,
+    San,
+}
+
+pub const PGN_FORMATS: [PgnFormat; 4] = [
+    PgnFormat::Pgn,
+    PgnFormat::Fen,
+    PgnFormat::Uci,
+    PgnFormat::San,
+];
+
+impl PgnFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PgnFormat::Pgn => "pgn",
+            PgnFormat::Fen => "fen",
+            PgnFormat::Uci => "uci",
+            PgnFormat::San => "san",
+        }
+    }
+
+    /// the format by its name, case insensitive
+    pub fn from_name(name: &str) -> Option<PgnFormat> {
+        let name = name.to_lowercase();
+        PGN_FORMATS
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+    }
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct PgnOptions {
+    /// the pgn file, - reads it from the standard input
+    pub file: String,
+    pub format: PgnFormat,
+    pub validate: bool,
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct PuzzleOptions {
+    /// the puzzles to solve instead of the builtin ones
+    pub file: Option<String>,
+    /// the puzzle to start from, counting from 1
+    pub number: Option<usize>,
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct BenchOptions {
+    pub depth: Option<u8>,
+}
+
+/// parse the command line arguments that follow the program name, the arguments without
+/// a command are the options of play
+pub fn parse_subcommand(args: &[String]) -> Result<Subcommand, String> {
+    let (name, rest) = match args.split_first() {
+        Some((name, rest)) if !name.starts_with('-') => (name.as_str(), rest),
+        _ => ("", args),
+    };
+    let usage = match name {
+        "" => USAGE,
+        "play" => PLAY_USAGE,
+        "uci" => UCI_USAGE,
+        "perft" => PERFT_USAGE,
+        "analyze" => ANALYZE_USAGE,
+        "pgn" => PGN_USAGE,
+        "puzzle" => PUZZLE_USAGE,
+        "bench" => BENCH_USAGE,
+        "help" => return Ok(Subcommand::Help(USAGE)),
+        _ => return Err(format!("Unknown Command '{}'\n{}", name, USAGE)),
+    };
+    if rest.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Subcommand::Help(usage));
+    }
+
+    let mut flags = Flags::new(rest, usage);
+    let subcommand = match name {
+        "" | "play" => Subcommand::Play(parse_game_options(rest)?),
+        "uci" => Subcommand::Uci,
+        "perft" => {
+            let mut options = PerftOptions::default();
+            let mut depth = None;
+            while let Some(flag) = flags.next_flag() {
+                match flag {
+                    "--fen" => options.fen = Some(flags.value(flag)?.to_string()),
+                    "--mode" => options.mode = Some(parse_mode(flags.value(flag)?)?),
+                    "--divide" => options.divide = true,
+                    flag if depth.is_none() && !flag.starts_with('-') => {
+                        depth = Some(parse_depth(flag)?)
+                    }
+                    flag => return Err(flags.unknown(flag)),
+                }
+            }
+            options.depth = depth.ok_or(format!("perft Needs A Depth\n{}", usage))?;
+            Subcommand::Perft(options)
+        }
+        "analyze" => {
+            let mut options = AnalyzeOptions::default();
+            while let Some(flag) = flags.next_flag() {
+                match flag {
+                    "--fen" => options.fen = Some(flags.value(flag)?.to_string()),
+                    "--moves" => options.moves.extend(split_moves(flags.value(flag)?)),
+                    "--mode" => options.mode = Some(parse_mode(flags.value(flag)?)?),
+                    "--depth" => options.depth = Some(parse_depth(flags.value(flag)?)?),
+                    flag => return Err(flags.unknown(flag)),
+                }
+            }
+            Subcommand::Analyze(options)
+        }
+        "pgn" => {
+            let mut options = PgnOptions::default();
+            let mut file = None;
+            while let Some(flag) = flags.next_flag() {
+                match flag {
+                    "--to" => {
+                        let name = flags.value(flag)?;
+                        options.format = PgnFormat::from_name(name).ok_or(format!(
+                            "Unknown Format '{}', Use pgn, fen, uci Or san",
+                            name
+                        ))?;
+                    }
+                    "--validate" => options.validate = true,
+                    flag if file.is_none() && (flag == "-" || !flag.starts_with('-')) => {
+                        file = Some(flag.to_string())
+                    }
+                    flag => return Err(flags.unknown(flag)),
+                }
+            }
+            options.file = file.ok_or(format!(
+                "pgn Needs A File, - Reads The Standard Input\n{}",
+                usage
+            ))?;
+            Subcommand::Pgn(options)
+        }
+        "puzzle" => {
+            let mut options = PuzzleOptions::default();
+            while let Some(flag) = flags.next_flag() {
+                match flag {
+                    "--file" => options.file = Some(flags.value(flag)?.to_string()),
+                    "--number" => {
+                        let number = flags.value(flag)?;
+                        options.number =
+                            Some(number.parse().ok().filter(|number| *number > 0).ok_or(
+                                format!("The Puzzle Number Must Be Positive, Not '{}'", number),
+                            )?);
+                    }
+                    flag => return Err(flags.unknown(flag)),
+                }
+            }
+            Subcommand::Puzzle(options)
+        }
+        // bench, the other names were turned down with the usage
+        _ => {
+            let mut options = BenchOptions::default();
+            while let Some(flag) = flags.next_flag() {
+                match flag {
+                    "--depth" => options.depth = Some(parse_depth(flags.value(flag)?)?),
+                    flag => return Err(flags.unknown(flag)),
+                }
+            }
+            Subcommand::Bench(options)
+        }
+    };
+    Ok(subcommand)
+}
+
+/// the arguments of a command read one after the other
+struct Flags<'a> {
+    args: std::slice::Iter<'a, String>,
+    usage: &'static str,
+}
+
+impl<'a> Flags<'a> {
+    fn new(args: &'a [String], usage: &'static str) -> Self {
+        Flags {
+            args: args.iter(),
+            usage,
+        }
+    }
+
+    fn next_flag(&mut self) -> Option<&'a str> {
+        self.args.next().map(String::as_str)
+    }
+
+    /// the value that follows the flag
+    fn value(&mut self, flag: &str) -> Result<&'a str, String> {
+        self.next_flag()
+            .ok_or(format!("{} Needs A Value\n{}", flag, self.usage))
+    }
+
+    fn unknown(&self, flag: &str) -> String {
+        format!("Unknown Option '{}'\n{}", flag, self.usage)
+    }
+}
+
+fn parse_mode(name: &str) -> Result<VariantKind, String> {
+    VariantKind::from_name(name).ok_or(format!("Unknown Mode '{}'", name))
+}
+
+fn parse_depth(depth: &str) -> Result<u8, String> {
+    depth
+        .parse()
+        .ok()
+        .filter(|depth| (1..=MAX_DEPTH).contains(depth))
+        .ok_or(format!(
+            "The Depth Is Between 1 And {}, Not '{}'",
+            MAX_DEPTH, depth
+        ))
+}
+
+fn split_moves(moves: &str) -> impl Iterator<Item = String> + '_ {
+    moves
+        .split(|separator: char| separator == ',' || separator.is_whitespace())
+        .filter(|chess_move| !chess_move.is_empty())
+        .map(String::from)
+}
+
+/// the nodes counted per second, zero when no time was measured
+fn nodes_per_second(nodes: u64, elapsed: Duration) -> u64 {
+    match elapsed.as_secs_f64() {
+        seconds if seconds > 0.0 => (nodes as f64 / seconds) as u64,
+        _ => 0,
+    }
+}
+
+/// count the positions after depth moves and write the count, each first move before
+/// it when dividing
+pub fn run_perft(options: &PerftOptions, output: &mut impl Write) -> i32 {
+    let mut board = match start_board(options.fen.as_deref(), options.mode) {
+        Ok(board) => board,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return EXIT_USAGE;
+        }
+    };
+
+    let started = Instant::now();
+    let nodes = if options.divide {
+        let mut counts: Vec<(String, u64)> = divide(&mut board, options.depth)
+            .iter()
+            .map(|(chess_move, nodes)| (board.move_to_uci(chess_move), *nodes))
+            .collect();
+        counts.sort();
+        for (uci, nodes) in &counts {
+            let _ = writeln!(output, "{}: {}", uci, nodes);
+        }
+        counts.iter().map(|(_, nodes)| nodes).sum()
+    } else {
+        perft(&mut board, options.depth)
+    };
+    let elapsed = started.elapsed();
+
+    let _ = writeln!(output, "Nodes: {}", nodes);
+    let _ = writeln!(
+        output,
+        "Time: {} ms, {} Nodes/s",
+        elapsed.as_millis(),
+        nodes_per_second(nodes, elapsed)
+    );
+    EXIT_SUCCESS
+}
+
+/// play the moves and search the best move of the position they reach
+pub fn run_analyze(options: &AnalyzeOptions, output: &mut impl Write) -> i32 {
+    let board = match start_board(options.fen.as_deref(), options.mode) {
+        Ok(board) => board,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return EXIT_USAGE;
+        }
+    };
+    let mut session = GameSession::from_board(board);
+    let exit_code = play_moves(&mut session, &options.moves);
+    if exit_code != EXIT_SUCCESS {
+        return exit_code;
+    }
+
+    let _ = writeln!(output, "{}", session.board().to_fen());
+    let _ = writeln!(output, "{}", session.status());
+    if session.result().is_some() {
+        return EXIT_SUCCESS;
+    }
+
+    let mut board = session.board().clone();
+    let depth = options.depth.unwrap_or(DEFAULT_DEPTH);
+    let started = Instant::now();
+    if let Some((chess_move, score)) = engine::search(&mut board, depth) {
+        let side_to_move = board.side_to_move();
+        // the score of the search is for the side to move, it is shown for white
+        let score = if side_to_move == Color::White {
+            score
+        } else {
+            -score
+        };
+        let evaluation = if engine::is_mate_score(score) {
+            let winner = if score > 0 { "White" } else { "Black" };
+            format!("{} Mates", winner)
+        } else {
+            format!("{:+.2}", score as f64 / 100.0)
+        };
+        let _ = writeln!(
+            output,
+            "Best Move: {} ({})",
+            move_to_san(&mut board, &chess_move),
+            board.move_to_uci(&chess_move)
+        );
+        let _ = writeln!(output, "Evaluation: {}", evaluation);
+    }
+    let _ = writeln!(
+        output,
+        "Depth: {}, Time: {} ms",
+        depth,
+        started.elapsed().as_millis()
+    );
+    EXIT_SUCCESS
+}
+
+/// play the pgn game again and write it in the format, or only tell whether it is valid
+pub fn run_pgn(options: &PgnOptions, input: &mut impl BufRead, output: &mut impl Write) -> i32 {
+    let text = if options.file == "-" {
+        let mut text = String::new();
+        input.read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(&options.file)
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Error: Can't Read The Game {}, {}", options.file, err);
+            return EXIT_USAGE;
+        }
+    };
+
+    let game = parse_pgn(&text);
+    let board = game.clone().and_then(|game| game.board());
+    let (game, board) = match (game, board) {
+        (Ok(game), Ok(board)) => (game, board),
+        (Err(err), _) | (_, Err(err)) => {
+            eprintln!("Error: {}", err);
+            return match err {
+                PgnError::IllegalMove { .. } => EXIT_ILLEGAL_MOVE,
+                _ => EXIT_USAGE,
+            };
+        }
+    };
+
+    if options.validate {
+        let _ = writeln!(
+            output,
+            "Valid, {} Half Moves, Result {}",
+            board.history().len(),
+            game.result
+        );
+        return EXIT_SUCCESS;
+    }
+
+    let text = match options.format {
+        PgnFormat::Fen => board.to_fen(),
+        PgnFormat::Pgn => {
+            // the variant and the start position are written from the board
+            let tags: Vec<(&str, String)> = game
+                .tags
+                .iter()
+                .filter(|(name, _)| !matches!(name.as_str(), "Variant" | "SetUp" | "FEN"))
+                .map(|(name, value)| (name.as_str(), value.clone()))
+                .collect();
+            write_pgn(&board, &tags, &game.result)
+                .trim_end()
+                .to_string()
+        }
+        PgnFormat::Uci | PgnFormat::San => {
+            let mut replay = board.clone();
+            while replay.undo().is_ok() {}
+            let mut moves = Vec::new();
+            for turn in board.history() {
+                moves.push(match options.format {
+                    PgnFormat::San => move_to_san(&mut replay, &turn.chess_move),
+                    _ => replay.move_to_uci(&turn.chess_move),
+                });
+                let _ = replay.handle_move(&turn.chess_move);
+            }
+            moves.join(" ")
+        }
+    };
+    let _ = writeln!(output, "{}", text);
+    EXIT_SUCCESS
+}
+
+/// time perft on the bench positions and a search of the start position
+pub fn run_bench(options: &BenchOptions, output: &mut impl Write) -> i32 {
+    let depth = options.depth.unwrap_or(DEFAULT_DEPTH);
+    let mut total_nodes = 0;
+    let mut total_time = Duration::ZERO;
+    for (name, fen_string) in BENCH_POSITIONS {
+        let mut board = BoardManager::new_from_fen(fen_string);
+        let started = Instant::now();
+        let nodes = perft(&mut board, depth);
+        let elapsed = started.elapsed();
+        total_nodes += nodes;
+        total_time += elapsed;
+        let _ = writeln!(
+            output,
+            "perft {:<10} {:>10} Nodes {:>8} ms",
+            name,
+            nodes,
+            elapsed.as_millis()
+        );
+    }
+    let _ = writeln!(
+        output,
+        "perft total      {:>10} Nodes {:>8} ms, {} Nodes/s",
+        total_nodes,
+        total_time.as_millis(),
+        nodes_per_second(total_nodes, total_time)
+    );
+
+    let mut board = BoardManager::new();
+    let started = Instant::now();
+    let best_move = engine::best_move(&mut board, depth).map_or("none".to_string(), |chess_move| {
+        board.move_to_uci(&chess_move)
+    });
+    let _ = writeln!(
+        output,
+        "search start     depth {} best {} {:>8} ms",
+        depth,
+        best_move,
+        started.elapsed().as_millis()
+    );
+    EXIT_SUCCESS
+}
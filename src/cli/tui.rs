@@ -0,0 +1,587 @@
+use std::io::{self, Read, Write};
+use std::process::{Command as Process, Stdio};
+use std::time::Duration;
+
+use crate::chess::board_manager::{opposite_color, BoardManager};
+use crate::chess::parse::{position_to_algebraic_notation, ChessMove};
+use crate::chess::piece::{Color, Piece, Position};
+
+use super::command::Command;
+use super::render::{self, piece_foreground, sgr, Palette, RESET};
+use super::session::GameSession;
+
+/// the keys of the help line at the bottom of the screen
+const KEYS_HELP: &str =
+    "arrows/hjkl move, enter/space select, click or drag with the mouse, esc cancel, u undo, f flip, q quit";
+
+/// the screen line of the top row of the board, below the column letters
+const BOARD_FIRST_LINE: u16 = 2;
+/// the screen column of the left square of the board, after the row numbers
+const BOARD_FIRST_COLUMN: u16 = 4;
+/// every square is a piece between two spaces
+const SQUARE_WIDTH: u16 = 3;
+
+/// how many lines of the move list are shown next to the board
+const MOVE_LIST_LINES: usize = 8;
+
+/// what the mouse did, only the left button is reported
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum MouseAction {
+    Press,
+    /// the mouse moved while the button is held
+    Drag,
+    Release,
+}
+
+/// a mouse report of the terminal, the screen line and column start at 1
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Mouse {
+    pub action: MouseAction,
+    pub column: u16,
+    pub line: u16,
+}
+
+/// a key pressed by the player in raw terminal mode
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    /// ctrl-c, raw mode delivers it as a key instead of a signal
+    Interrupt,
+    Mouse(Mouse),
+    Char(char),
+}
+
+/// split the bytes of a terminal read to keys, the arrows come as escape sequences
+/// and the mouse as sgr 1006 reports, ESC [ < button ; column ; line M or m on release
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index..].starts_with(b"\x1b[<") {
+            let report_length = bytes[index..]
+                .iter()
+                .position(|byte| *byte == b'M' || *byte == b'm');
+            if let Some(report_length) = report_length {
+                let report = &bytes[index + 3..index + report_length + 1];
+                keys.extend(parse_mouse(report).map(Key::Mouse));
+                index += report_length + 1;
+                continue;
+            }
+        }
+        let key = match bytes[index] {
+            0x1b if bytes.get(index + 1) == Some(&b'[') && index + 2 < bytes.len() => {
+                index += 2;
+                match bytes[index] {
+                    b'A' => Key::Up,
+                    b'B' => Key::Down,
+                    b'C' => Key::Right,
+                    b'D' => Key::Left,
+                    _ => Key::Escape,
+                }
+            }
+            0x1b => Key::Escape,
+            b'\r' | b'\n' => Key::Enter,
+            0x03 => Key::Interrupt,
+            byte => Key::Char(byte as char),
+        };
+        keys.push(key);
+        index += 1;
+    }
+    keys
+}
+
+/// parse the part of a sgr mouse report after ESC [ <, for example 0;12;5M,
+/// None for the other buttons and the wheel
+fn parse_mouse(report: &[u8]) -> Option<Mouse> {
+    let (last, fields) = report.split_last()?;
+    let fields = std::str::from_utf8(fields).ok()?;
+    let mut numbers = fields.split(';').map(|number| number.parse::<u16>().ok());
+    let (button, column, line) = (numbers.next()??, numbers.next()??, numbers.next()??);
+    // the low bits are the button, 32 is added on motion and 64 for the wheel
+    if button & 0b11 != 0 || button & 64 != 0 {
+        return None;
+    }
+    let action = match (last, button & 32 != 0) {
+        (b'm', _) => MouseAction::Release,
+        (_, true) => MouseAction::Drag,
+        _ => MouseAction::Press,
+    };
+    Some(Mouse {
+        action,
+        column,
+        line,
+    })
+}
+
+/// the full screen board, a cursor moved with the keys selects a piece and its destination
+pub struct Tui {
+    cursor: Position,
+    selected: Option<Position>,
+    /// the legal moves of the selected piece
+    targets: Vec<ChessMove>,
+    /// the moves waiting for the player to choose the promotion piece
+    promotions: Vec<ChessMove>,
+    /// the square the mouse button went down on, a release elsewhere drops the piece there
+    pressed: Option<Position>,
+    message: String,
+    /// a command went through since the last time it was asked
+    executed: bool,
+}
+
+impl Tui {
+    /// start with the cursor on the king of the side to move
+    pub fn new(board: &BoardManager) -> Self {
+        let side_to_move = board.side_to_move();
+        let cursor = if board.has_king(side_to_move) {
+            board.king_position(side_to_move)
+        } else {
+            Position::new(0, 0)
+        };
+        Tui {
+            cursor,
+            selected: None,
+            targets: Vec::new(),
+            promotions: Vec::new(),
+            pressed: None,
+            message: String::new(),
+            executed: false,
+        }
+    }
+
+    pub fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    pub fn selected(&self) -> Option<Position> {
+        self.selected
+    }
+
+    /// the destinations of the selected piece
+    pub fn targets(&self) -> Vec<Position> {
+        self.targets
+            .iter()
+            .map(|chess_move| chess_move.piece_dest)
+            .collect()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// whether a command went through since the last call
+    pub fn take_executed(&mut self) -> bool {
+        std::mem::take(&mut self.executed)
+    }
+
+    /// act on the key, false once the player asked to leave
+    pub fn handle_key(&mut self, session: &mut GameSession, key: Key) -> bool {
+        if let Key::Mouse(mouse) = key {
+            self.handle_mouse(session, mouse);
+            return true;
+        }
+        if !self.promotions.is_empty() {
+            self.choose_promotion(session, key);
+            return true;
+        }
+        let board = session.board();
+        let up = if board.white_in_front() { 1 } else { -1 };
+        match key {
+            Key::Up | Key::Char('k') => self.move_cursor(board, up, 0),
+            Key::Down | Key::Char('j') => self.move_cursor(board, -up, 0),
+            Key::Left | Key::Char('h') => self.move_cursor(board, 0, -1),
+            Key::Right | Key::Char('l') => self.move_cursor(board, 0, 1),
+            Key::Enter | Key::Char(' ') => self.select(session),
+            Key::Escape => self.clear_selection(),
+            Key::Char('u') => self.run(session, Command::Undo),
+            Key::Char('f') => self.run(session, Command::Flip),
+            Key::Char('q') | Key::Interrupt => return false,
+            Key::Char(_) => self.message = format!("Keys: {}", KEYS_HELP),
+            Key::Mouse(_) => {}
+        }
+        true
+    }
+
+    /// a press selects like enter, releasing the button on another square drops the piece there
+    fn handle_mouse(&mut self, session: &mut GameSession, mouse: Mouse) {
+        if !self.promotions.is_empty() {
+            if mouse.action == MouseAction::Press {
+                match self.popup_index(session.board(), &mouse) {
+                    Some(index) => self.play(session, self.promotions[index]),
+                    None => self.clear_selection(),
+                }
+            }
+            return;
+        }
+
+        let position = match self.position_at(session.board(), mouse.column, mouse.line) {
+            Some(position) => position,
+            None => {
+                if mouse.action == MouseAction::Press {
+                    self.clear_selection();
+                }
+                return;
+            }
+        };
+        self.cursor = position;
+        match mouse.action {
+            MouseAction::Press => {
+                self.pressed = Some(position);
+                self.select(session);
+            }
+            MouseAction::Drag => {}
+            MouseAction::Release => {
+                let dragged = self.pressed.is_some_and(|pressed| pressed != position)
+                    && self.selected == self.pressed;
+                if dragged && self.targets().contains(&position) {
+                    self.select(session);
+                }
+                self.pressed = None;
+            }
+        }
+    }
+
+    /// the square shown on the screen cell
+    pub fn position_at(&self, board: &BoardManager, column: u16, line: u16) -> Option<Position> {
+        if line < BOARD_FIRST_LINE || column < BOARD_FIRST_COLUMN {
+            return None;
+        }
+        let size = board.size();
+        let line_index = (line - BOARD_FIRST_LINE) as usize;
+        let column_index = ((column - BOARD_FIRST_COLUMN) / SQUARE_WIDTH) as usize;
+        if line_index >= size.row_count() || column_index >= size.column_count() {
+            return None;
+        }
+        let row = if board.white_in_front() {
+            size.row_count() - 1 - line_index
+        } else {
+            line_index
+        };
+        Some(Position::new(row as i8, column_index as i8))
+    }
+
+    /// the screen line and column of the left cell of the square
+    fn screen_cell(&self, board: &BoardManager, position: &Position) -> (u16, u16) {
+        let line_index = if board.white_in_front() {
+            board.size().last_row() - position.x
+        } else {
+            position.x
+        };
+        (
+            BOARD_FIRST_LINE + line_index as u16,
+            BOARD_FIRST_COLUMN + position.y as u16 * SQUARE_WIDTH,
+        )
+    }
+
+    /// the promotion popup covers squares of the row of the destination, starting at its
+    /// column as long as the pieces fit on the board
+    fn popup_origin(&self, board: &BoardManager) -> (u16, u16) {
+        let dest = self.promotions[0].piece_dest;
+        let column = dest
+            .y
+            .min(board.size().column_count() as i8 - self.promotions.len() as i8);
+        self.screen_cell(board, &Position::new(dest.x, column.max(0)))
+    }
+
+    /// the promotion piece of the popup under the mouse
+    fn popup_index(&self, board: &BoardManager, mouse: &Mouse) -> Option<usize> {
+        let (line, column) = self.popup_origin(board);
+        if mouse.line != line || mouse.column < column {
+            return None;
+        }
+        let index = ((mouse.column - column) / SQUARE_WIDTH) as usize;
+        (index < self.promotions.len()).then_some(index)
+    }
+
+    /// the promotion pieces drawn over the board
+    fn render_popup(&self, board: &BoardManager, palette: &Palette) -> String {
+        let (line, column) = self.popup_origin(board);
+        let mut popup = format!("\x1b[{};{}H", line, column);
+        let side_to_move = board.side_to_move();
+        for chess_move in &self.promotions {
+            if let Some(p_type) = chess_move.prompted {
+                popup.push_str(&format!(
+                    "{} {} {}",
+                    sgr(&[palette.popup, piece_foreground(palette, side_to_move)]),
+                    board
+                        .renderer()
+                        .piece_char(&Piece::new(p_type, side_to_move)),
+                    RESET
+                ));
+            }
+        }
+        popup
+    }
+
+    fn move_cursor(&mut self, board: &BoardManager, dx: i8, dy: i8) {
+        let position = Position::new(self.cursor.x + dx, self.cursor.y + dy);
+        if board.is_inside_board(&position) {
+            self.cursor = position;
+        }
+    }
+
+    fn clear_selection(&mut self) {
+        self.selected = None;
+        self.targets.clear();
+        self.promotions.clear();
+        self.message.clear();
+    }
+
+    /// select the piece under the cursor, or move the selected piece to the cursor
+    fn select(&mut self, session: &mut GameSession) {
+        let moves: Vec<ChessMove> = self
+            .targets
+            .iter()
+            .filter(|chess_move| chess_move.piece_dest == self.cursor)
+            .copied()
+            .collect();
+        match moves.as_slice() {
+            [] => {}
+            [chess_move] => return self.play(session, *chess_move),
+            _ => {
+                let letters: Vec<String> = moves
+                    .iter()
+                    .filter_map(|chess_move| chess_move.prompted)
+                    .map(|p_type| p_type.to_fen_char().to_string())
+                    .collect();
+                self.message = format!(
+                    "Promote To: {}, Or Click One, esc Cancels",
+                    letters.join(" ")
+                );
+                self.promotions = moves;
+                return;
+            }
+        }
+
+        let board = session.board();
+        match board.piece_at(&self.cursor) {
+            Some(piece) if piece.p_color == board.side_to_move() => {
+                self.targets = board.clone().legal_moves_from(&self.cursor);
+                self.selected = Some(self.cursor);
+                self.message = if self.targets.is_empty() {
+                    "That Piece Has No Legal Moves".to_string()
+                } else {
+                    String::new()
+                };
+            }
+            _ if self.selected.is_some() => {
+                self.message = "Not A Legal Destination, esc Cancels".to_string();
+            }
+            _ => self.message = "Select A Piece Of The Side To Move".to_string(),
+        }
+    }
+
+    fn choose_promotion(&mut self, session: &mut GameSession, key: Key) {
+        let chosen = match key {
+            Key::Char(letter) => self
+                .promotions
+                .iter()
+                .find(|chess_move| {
+                    chess_move
+                        .prompted
+                        .is_some_and(|p_type| p_type.to_fen_char() == letter.to_ascii_lowercase())
+                })
+                .copied(),
+            _ => None,
+        };
+        match chosen {
+            Some(chess_move) => self.play(session, chess_move),
+            None if key == Key::Escape => self.clear_selection(),
+            None => {}
+        }
+    }
+
+    fn play(&mut self, session: &mut GameSession, chess_move: ChessMove) {
+        let mover = session.board().side_to_move();
+        let uci_move = session.board().move_to_uci(&chess_move);
+        self.run(session, Command::Move(chess_move));
+        if session.board().side_to_move() != mover {
+            self.message = format!("{:?} Played {}", mover, uci_move);
+        }
+    }
+
+    /// run the command on the session, only its errors are shown as the board shows the rest
+    fn run(&mut self, session: &mut GameSession, command: Command) {
+        self.clear_selection();
+        match session.execute(command) {
+            Ok(_) => self.executed = true,
+            Err(err) => self.message = format!("Error: {}", err),
+        }
+    }
+
+    /// the whole screen, the board with the panels at its right and the status below
+    pub fn render(&self, session: &GameSession) -> String {
+        let board = session.board();
+        // the terminal board needs highlights, so the plain theme shows the monochrome ones
+        let palette = session.theme().palette();
+        let board_lines = self.board_lines(board, &palette);
+        let panel_lines = self.panel_lines(session);
+
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        for line_number in 0..board_lines.len().max(panel_lines.len()) {
+            let board_line = board_lines.get(line_number).map_or("", String::as_str);
+            let panel_line = panel_lines.get(line_number).map_or("", String::as_str);
+            let padding = if board_line.is_empty() {
+                " ".repeat(board.size().column_count() * 3 + 6)
+            } else {
+                String::new()
+            };
+            screen.push_str(&format!("{}{}   {}\r\n", board_line, padding, panel_line));
+        }
+
+        let side_to_move = board.side_to_move();
+        let status = match session.result() {
+            Some(result) => result.to_string(),
+            None if board.is_check(side_to_move) => format!("{:?} To Move, Check", side_to_move),
+            None => format!("{:?} To Move", side_to_move),
+        };
+        screen.push_str(&format!(
+            "\r\n{}\r\n{}\r\n{}\r\n",
+            status, self.message, KEYS_HELP
+        ));
+        if !self.promotions.is_empty() {
+            screen.push_str(&self.render_popup(board, &palette));
+        }
+        screen
+    }
+
+    /// the board with the cursor, the selected piece and its destinations highlighted
+    fn board_lines(&self, board: &BoardManager, palette: &Palette) -> Vec<String> {
+        render::board_lines(board, palette, board.orientation(), |position| {
+            if *position == self.cursor {
+                Some(palette.cursor)
+            } else if self.selected == Some(*position) {
+                Some(palette.selected)
+            } else if self
+                .targets
+                .iter()
+                .any(|chess_move| chess_move.piece_dest == *position)
+            {
+                Some(palette.target)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// the move list, the clocks and the captured material
+    fn panel_lines(&self, session: &GameSession) -> Vec<String> {
+        let board = session.board();
+        let side_to_move = board.side_to_move();
+        let mut lines = vec![format!(
+            "Cursor {}   Clocks: White {}  Black {}",
+            position_to_algebraic_notation(&self.cursor),
+            format_duration(session.clocks().shown(Color::White, side_to_move)),
+            format_duration(session.clocks().shown(Color::Black, side_to_move))
+        )];
+
+        for color in [Color::White, Color::Black] {
+            lines.push(format!(
+                "{:?} Captured: {}",
+                color,
+                render::captures_text(board, color)
+            ));
+        }
+
+        lines.push("Moves:".to_string());
+        let history = board.history();
+        let first_mover = if history.len().is_multiple_of(2) {
+            side_to_move
+        } else {
+            opposite_color(side_to_move)
+        };
+        let mut plies: Vec<String> = history
+            .iter()
+            .map(|turn| turn.chess_move.to_string())
+            .collect();
+        if first_mover == Color::Black && !plies.is_empty() {
+            plies.insert(0, "...".to_string());
+        }
+        let move_lines: Vec<String> = plies
+            .chunks(2)
+            .enumerate()
+            .map(|(number, pair)| format!("{:>3}. {}", number + 1, pair.join(" ")))
+            .collect();
+        let skipped = move_lines.len().saturating_sub(MOVE_LIST_LINES);
+        lines.extend(move_lines.into_iter().skip(skipped));
+        lines
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// the terminal in raw mode on the alternate screen, restored when dropped
+struct RawTerminal {
+    saved_mode: String,
+}
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        let saved_mode = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        // the alternate screen, a hidden cursor and sgr mouse reports of presses and drags
+        print!("\x1b[?1049h\x1b[?25l\x1b[?1002h\x1b[?1006h");
+        io::stdout().flush()?;
+        Ok(RawTerminal {
+            saved_mode: saved_mode.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[?1006l\x1b[?1002l\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved_mode]);
+    }
+}
+
+/// run stty on the terminal of the standard input and return what it printed
+fn stty(arguments: &[&str]) -> io::Result<String> {
+    let output = Process::new("stty")
+        .args(arguments)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("The Terminal Can't Switch To Raw Mode"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// play the session on the full screen board until the player quits, after_command runs
+/// after every command that went through and its error is shown below the board
+pub fn run_tui(
+    session: &mut GameSession,
+    mut after_command: impl FnMut(&GameSession) -> Result<(), String>,
+) -> io::Result<()> {
+    let _terminal = RawTerminal::enter()?;
+    let mut tui = Tui::new(session.board());
+    let mut stdout = io::stdout();
+    let mut buffer = [0; 256];
+    loop {
+        write!(stdout, "{}", tui.render(session))?;
+        stdout.flush()?;
+        let count = io::stdin().read(&mut buffer)?;
+        if count == 0 {
+            return Ok(());
+        }
+        for key in parse_keys(&buffer[..count]) {
+            let running = tui.handle_key(session, key);
+            if tui.take_executed() {
+                if let Err(err) = after_command(session) {
+                    tui.message = format!("Error: {}", err);
+                }
+            }
+            if !running {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::{self, BufRead, Write};
+
+use crate::chess::board_manager::BoardManager;
+use crate::chess::engine;
+use crate::chess::parse::parse_chess_move;
+
+/// how many moves ahead the engine looks when go doesn't say
+const DEFAULT_DEPTH: u8 = 3;
+
+/// the engine side of the universal chess interface, reads commands from a chess
+/// program and answers with the lines to send back
+pub struct UciEngine {
+    board: BoardManager,
+    chess960: bool,
+    running: bool,
+}
+
+impl Default for UciEngine {
+    fn default() -> Self {
+        UciEngine {
+            board: BoardManager::new(),
+            chess960: false,
+            running: true,
+        }
+    }
+}
+
+impl UciEngine {
+    pub fn new() -> Self {
+        UciEngine::default()
+    }
+
+    /// false once quit was received
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn board(&self) -> &BoardManager {
+        &self.board
+    }
+
+    /// handle a line sent by the chess program, unknown commands are ignored as the protocol asks
+    pub fn handle_line(&mut self, line: &str) -> Vec<String> {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("uci") => vec![
+                format!("id name cte {}", env!("CARGO_PKG_VERSION")),
+                "id author yotam5".to_string(),
+                "option name UCI_Chess960 type check default false".to_string(),
+                "uciok".to_string(),
+            ],
+            Some("isready") => vec!["readyok".to_string()],
+            Some("setoption") => {
+                self.set_option(&words.collect::<Vec<&str>>());
+                Vec::new()
+            }
+            Some("ucinewgame") => {
+                self.board = BoardManager::new();
+                self.board.set_chess960(self.chess960);
+                Vec::new()
+            }
+            Some("position") => match self.set_position(&words.collect::<Vec<&str>>()) {
+                Ok(()) => Vec::new(),
+                Err(err) => vec![format!("info string {}", err)],
+            },
+            Some("go") => vec![self.go(&words.collect::<Vec<&str>>())],
+            Some("quit") => {
+                self.running = false;
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// setoption name <name> value <value>
+    fn set_option(&mut self, arguments: &[&str]) {
+        if let ["name", "UCI_Chess960", "value", value] = arguments {
+            self.chess960 = *value == "true";
+            self.board.set_chess960(self.chess960);
+        }
+    }
+
+    /// position [startpos | fen <fen>] [moves <move>...]
+    fn set_position(&mut self, arguments: &[&str]) -> Result<(), String> {
+        let moves_index = arguments
+            .iter()
+            .position(|argument| *argument == "moves")
+            .unwrap_or(arguments.len());
+
+        let mut board = match arguments.first() {
+            Some(&"startpos") => BoardManager::new(),
+            Some(&"fen") => BoardManager::try_from_fen(&arguments[1..moves_index].join(" "))
+                .map_err(|err| err.to_string())?,
+            _ => return Err("position Needs startpos Or fen".to_string()),
+        };
+        board.set_chess960(self.chess960 || board.is_chess960());
+
+        for chess_move in arguments.iter().skip(moves_index + 1) {
+            let parsed_move = parse_chess_move(chess_move).map_err(|err| err.to_string())?;
+            board
+                .handle_move(&parsed_move)
+                .map_err(|err| format!("{} {}", chess_move, err))?;
+        }
+        self.board = board;
+        Ok(())
+    }
+
+    /// go [depth <n>], other search limits are not supported and ignored
+    fn go(&mut self, arguments: &[&str]) -> String {
+        let depth = arguments
+            .iter()
+            .position(|argument| *argument == "depth")
+            .and_then(|index| arguments.get(index + 1))
+            .and_then(|depth| depth.parse().ok())
+            .unwrap_or(DEFAULT_DEPTH)
+            .max(1);
+
+        let mut board = self.board.clone();
+        match engine::best_move(&mut board, depth) {
+            Some(chess_move) => format!("bestmove {}", self.board.move_to_uci(&chess_move)),
+            None => "bestmove 0000".to_string(),
+        }
+    }
+}
+
+/// talk uci over stdin and stdout until quit or the input ends
+pub fn run_uci(first_lines: &[&str]) {
+    let mut uci_engine = UciEngine::new();
+    let stdin = io::stdin();
+    let first_lines = first_lines
+        .iter()
+        .map(|line| Ok::<String, io::Error>(line.to_string()));
+    for line in first_lines.chain(stdin.lock().lines()) {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        for reply in uci_engine.handle_line(&line) {
+            println!("{}", reply);
+        }
+        let _ = io::stdout().flush();
+        if !uci_engine.is_running() {
+            break;
+        }
+    }
+}
@@ -1,85 +1,244 @@
-use std::io::{self, BufRead, BufReader, Read, Write};
-use std::ops::Index;
+use std::io::{self, BufRead, Write};
 
-use crate::parse::{is_valid_promotion, is_valid_uci_piece_character, parse_algebraic_notation};
 use chess::board_manager::BoardManager;
-use chess::parse;
-use chess::parse::ChessMove;
-use chess::piece::PieceType;
+use chess::renderer::{Orientation, Renderer};
+use chess::variant::VariantKind;
+use cli::autosave;
+use cli::batch::Report;
+use cli::clock::TimeControl;
+use cli::command::{parse_command, Command};
+use cli::render::{default_theme, Theme};
+use cli::session::GameSession;
+use cli::tui::run_tui;
+use cli::uci::run_uci;
 
-type MyResult<T> = Result<T, dyn std::error::Error>;
+type MyResult<T> = Result<T, Box<dyn std::error::Error>>;
 
 pub mod chess;
+pub mod cli;
 
-fn input_user_move() -> String {
-    let mut input = String::with_capacity(4);
-    io::stdout().flush().unwrap();
-    let stdin = io::stdin();
-    let mut bstdin = BufReader::new(stdin.take(6));
-    bstdin.read_line(&mut input).unwrap();
-    let _ = input.pop();
-    input
+/// read a line from the user, None when the input ended
+fn input_user_line() -> MyResult<Option<String>> {
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().lock().read_line(&mut input)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(input.trim().to_string()))
 }
 
-fn parse_chess_move(chess_move: &str) -> Result<ChessMove, Box<dyn std::error::Error>> {
-    let mut chess_move_chunks = chess_move.as_bytes().chunks(2);
-    if chess_move_chunks.len() < 2 || chess_move_chunks.len() > 3 {
-        Err("Chess Move Is Two Squares And Optional Promotion")?;
-    }
+pub const USAGE: &str = "usage: cte [<command>] [<options>]
+commands:
+  play      play in the terminal, the command used when none is given
+  uci       talk to a chess program over the universal chess interface
+  perft     count the positions reached after some moves
+  analyze   search the best move of a position
+  pgn       check a pgn game and convert it to fen, uci or san
+  puzzle    solve checkmate puzzles
+  bench     time the move generator and the search
+type cte <command> --help to see the options of a command";
 
-    let source_square = chess_move_chunks.next().unwrap();
-    let source_square = parse_algebraic_notation(source_square.index(0), source_square.index(1))?;
+pub const PLAY_USAGE: &str = "usage: cte play [--resume] [--mode <variant>] [--time <minutes[+seconds]>] \
+[--theme <name>] [--pieces <glyphs|letters|boxes|wide>] [--orientation <white|black|side|player>]
+       cte play [--mode <variant>] [--fen <fen>] [--moves <e2e4,e7e5,...>] [--script <file|->] \
+[--print <fen,board,status,legal>]
+the second form plays without asking anything and exits with 1 on an illegal move, 2 on wrong arguments";
 
-    let dest_square = chess_move_chunks.next().unwrap();
-    let dest_square = parse_algebraic_notation(dest_square.index(0), dest_square.index(1))?;
+/// the settings of the game given on the command line, the missing ones keep their default
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct GameOptions {
+    pub theme: Option<Theme>,
+    pub renderer: Option<Renderer>,
+    pub orientation: Option<Orientation>,
+    /// the variant of a new game
+    pub mode: Option<VariantKind>,
+    /// the time each player gets for the game
+    pub time_control: Option<TimeControl>,
+    /// continue the game saved automatically when the last one was left
+    pub resume: bool,
+    /// the position the batch mode starts from
+    pub fen: Option<String>,
+    /// the moves the batch mode plays, in uci or standard algebraic notation
+    pub moves: Vec<String>,
+    /// the file of the commands the batch mode runs, - reads them from the standard input
+    pub script: Option<String>,
+    /// what the batch mode prints at the end
+    pub reports: Vec<Report>,
+}
 
-    let promotion_chunk = chess_move_chunks.next();
-    let mut promotion_type = None;
+impl GameOptions {
+    /// the options ask to play without the interactive prompt
+    pub fn is_batch(&self) -> bool {
+        self.fen.is_some()
+            || !self.moves.is_empty()
+            || self.script.is_some()
+            || !self.reports.is_empty()
+    }
+}
 
-    if promotion_chunk.is_some() {
-        let promotion_chunk = promotion_chunk.unwrap();
-        if !is_valid_promotion(&promotion_chunk[0]) {
-            Err("Not A Valid Promotion Piece")?;
+/// parse the command line arguments that follow the program name
+pub fn parse_game_options(args: &[String]) -> Result<GameOptions, String> {
+    let mut options = GameOptions::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        if flag == "--resume" {
+            options.resume = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or(format!("{} Needs A Value\n{}", flag, PLAY_USAGE))?;
+        match flag.as_str() {
+            "--theme" => {
+                options.theme =
+                    Some(Theme::from_name(value).ok_or(format!("Unknown Theme '{}'", value))?)
+            }
+            "--pieces" => {
+                options.renderer = Some(
+                    Renderer::from_name(value).ok_or(format!("Unknown Renderer '{}'", value))?,
+                )
+            }
+            "--orientation" => {
+                options.orientation = Some(
+                    Orientation::from_name(value)
+                        .ok_or(format!("Unknown Orientation '{}'", value))?,
+                )
+            }
+            "--mode" => {
+                options.mode =
+                    Some(VariantKind::from_name(value).ok_or(format!("Unknown Mode '{}'", value))?)
+            }
+            "--time" => {
+                options.time_control = Some(TimeControl::from_name(value).ok_or(format!(
+                    "Unknown Time Control '{}', Give Minutes And Seconds Added Per Move Like 5+3",
+                    value
+                ))?)
+            }
+            "--fen" => options.fen = Some(value.clone()),
+            "--moves" => options.moves.extend(
+                value
+                    .split(|separator: char| separator == ',' || separator.is_whitespace())
+                    .filter(|chess_move| !chess_move.is_empty())
+                    .map(String::from),
+            ),
+            "--script" => options.script = Some(value.clone()),
+            "--print" => {
+                for name in value.split(',').filter(|name| !name.is_empty()) {
+                    options
+                        .reports
+                        .push(Report::from_name(name).ok_or(format!("Unknown Report '{}'", name))?);
+                }
+            }
+            _ => return Err(format!("Unknown Option '{}'\n{}", flag, PLAY_USAGE)),
         }
-        let piece_char = promotion_chunk[0] as char;
-        promotion_type = Some(piece_char.into());
     }
-
-    Ok(ChessMove::new(source_square, dest_square, promotion_type))
+    if options.resume && options.mode.is_some() {
+        return Err("--resume Continues The Saved Game, It Can't Start A New --mode".to_string());
+    }
+    Ok(options)
 }
 
+#[allow(dead_code)]
 fn clear_screen() {
     print!("\x1B[2J\x1B[1;1H");
 }
 
-fn get_user_move() -> ChessMove {
-    let mut move_info = input_user_move();
-    let mut parsed_move_info = parse_chess_move(&move_info);
-    while parsed_move_info.is_err() {
-        println!("{:?}", &parsed_move_info);
-        
-        move_info = input_user_move();
+/// the session to play, the saved game when resuming with the settings given on the
+/// command line over the saved ones
+fn start_session(options: GameOptions) -> MyResult<GameSession> {
+    let mut session = if options.resume {
+        autosave::resume()?
+    } else {
+        let board = BoardManager::new_variant(options.mode.unwrap_or_default());
+        let mut session = GameSession::from_board(board);
+        session.set_theme(default_theme());
+        session
+    };
+    session.apply_settings(options.theme, options.renderer, options.orientation);
+    if let Some(time_control) = options.time_control {
+        session.set_time_control(time_control);
+    }
+    Ok(session)
+}
+
+/// saves the game after the commands, a new game replaces the saved one only once a move
+/// was played in it so a forgotten --resume doesn't lose the saved game
+struct Autosaver {
+    enabled: bool,
+    /// the game is worth saving, it was resumed or a move was played
+    started: bool,
+}
 
-        parsed_move_info = parse_chess_move(&move_info);
+impl Autosaver {
+    /// save the game if it started, the first failure is returned and turns the saving off
+    fn save(&mut self, session: &GameSession) -> Result<(), String> {
+        self.started = self.started || !session.board().history().is_empty();
+        if !self.enabled || !self.started {
+            return Ok(());
+        }
+        autosave::autosave(session).map_err(|err| {
+            self.enabled = false;
+            format!("Autosave Failed, {}, The Game Won't Be Saved", err)
+        })
     }
-    parsed_move_info.unwrap()
 }
 
-pub fn run_game() {
-    let mut board = BoardManager::new();
-    //println!("{:?}", &board);
-    let mn = String::new();
-    let mut game_end = false;
-    //println!("{:?}", std::mem::size_of::<parse::AlgebraicNotation>());
-    println!("{}", &board);
-    while !game_end {
-        let move_info = get_user_move();
-        let move_result = board.handle_move(&move_info);
-        if move_result.is_err() {
-            println!("Illegal move: {:?}", &move_result);
+/// play in the terminal, the game is saved after every command so it can be resumed
+pub fn run_game(options: GameOptions) -> MyResult<()> {
+    let resume = options.resume;
+    let mut session = start_session(options)?;
+    let mut autosaver = Autosaver {
+        enabled: true,
+        started: resume,
+    };
+    if let Some(path) = autosave::autosave_path().filter(|path| !resume && path.is_file()) {
+        println!(
+            "A Saved Game Is In {}, Start With --resume To Continue It, It Is Replaced Once This Game Has A Move",
+            path.display()
+        );
+    }
+    println!("{}", session.render_board());
+    println!("Type help to see the commands");
+
+    loop {
+        print!("{:?} > ", session.board().side_to_move());
+        let input = match input_user_line() {
+            Ok(Some(input)) => input,
+            Ok(None) => break,
+            Err(err) => {
+                println!("Error: {}", err);
+                break;
+            }
+        };
+        if input.is_empty() {
             continue;
         }
+
+        match parse_command(&input) {
+            Ok(Command::Quit) => break,
+            Ok(Command::Uci) => {
+                run_uci(&["uci"]);
+                break;
+            }
+            Ok(Command::Tui) => {
+                if let Err(err) = run_tui(&mut session, |session| autosaver.save(session)) {
+                    println!("Error: {}", err);
+                }
+                println!("{}", session.render_board());
+            }
+            Ok(command) => match session.execute(command) {
+                Ok(output) => println!("{}", output),
+                Err(err) => println!("Error: {}", err),
+            },
+            Err(err) => {
+                println!("Error: {}", err);
+                continue;
+            }
+        }
+        if let Err(err) = autosaver.save(&session) {
+            println!("Error: {}", err);
+        }
         //clear_screen();
-        println!("{}", &board);
     }
+    Ok(())
 }
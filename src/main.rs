@@ -1,5 +1,53 @@
+use std::env;
+use std::io;
+use std::process;
+
+use cte::cli::batch::{run_batch, EXIT_USAGE};
+use cte::cli::puzzle::run_puzzles;
+use cte::cli::subcommand::{
+    parse_subcommand, run_analyze, run_bench, run_perft, run_pgn, Subcommand,
+};
+use cte::cli::uci::run_uci;
 use cte::run_game;
 
 fn main() {
-    run_game();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let subcommand = match parse_subcommand(&args) {
+        Ok(subcommand) => subcommand,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(EXIT_USAGE);
+        }
+    };
+
+    let exit_code = match subcommand {
+        Subcommand::Help(usage) => {
+            println!("{}", usage);
+            0
+        }
+        Subcommand::Play(options) if options.is_batch() => {
+            run_batch(&options, &mut io::stdin().lock(), &mut io::stdout().lock())
+        }
+        Subcommand::Play(options) => match run_game(options) {
+            Ok(()) => 0,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                1
+            }
+        },
+        Subcommand::Uci => {
+            run_uci(&[]);
+            0
+        }
+        Subcommand::Perft(options) => run_perft(&options, &mut io::stdout().lock()),
+        Subcommand::Analyze(options) => run_analyze(&options, &mut io::stdout().lock()),
+        Subcommand::Pgn(options) => {
+            run_pgn(&options, &mut io::stdin().lock(), &mut io::stdout().lock())
+        }
+        Subcommand::Puzzle(options) => {
+            run_puzzles(&options, &mut io::stdin().lock(), &mut io::stdout().lock())
+        }
+        Subcommand::Bench(options) => run_bench(&options, &mut io::stdout().lock()),
+    };
+    process::exit(exit_code);
 }
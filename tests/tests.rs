@@ -1,11 +1,22 @@
+use std::rc::Rc;
+
 use cte::chess;
-use cte::chess::board_manager::BoardManager;
-use cte::chess::parse::{parse_algebraic_notation, ChessMove};
-use cte::chess::piece::{Color, PieceType, Position};
+use cte::chess::board_manager::{BoardManager, GameStatus, Pin};
+use cte::chess::chess960;
+use cte::chess::engine;
+use cte::chess::error::{FenError, MoveError, ParseError, PieceCharError};
+use cte::chess::parse::{parse_algebraic_notation, parse_chess_move, parse_square, ChessMove};
+use cte::chess::perft::perft;
+use cte::chess::piece::{Color, PieceType, Position, PIECE_TYPES};
 use cte::chess::piece_movement::{
-    is_valid_bishop_move, is_valid_king_move, is_valid_knight_move, is_valid_pawn_move,
-    is_valid_queen_move, is_valid_rook_move,
+    is_valid_bishop_move, is_valid_king_move, is_valid_knight_move, is_valid_move,
+    is_valid_pawn_move, is_valid_queen_move, is_valid_rook_move, parse_betza, piece_atoms, Reach,
 };
+use cte::chess::renderer::Orientation;
+use cte::chess::variant::{Variant, VariantKind};
+use cte::cli::command::{parse_command, Command};
+use cte::cli::session::{GameResult, GameSession};
+use cte::cli::uci::UciEngine;
 //this wont be saved
 fn generate_algebraic_notation_arrays() -> ([char; 8], [char; 8]) {
     let letters = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
@@ -17,23 +28,23 @@ fn generate_algebraic_notation_arrays() -> ([char; 8], [char; 8]) {
 fn promote_pawn() {
     // todo! need to fix parsed, shifting to the left for some reason
     let mut board = BoardManager::new_from_fen("rnbqkbnr/1Pppp2p/8/8/4P3/8/pP1PP1PP/RNBQKBNR");
-    let src = parse_algebraic_notation(&b'b',&b'7').unwrap();
-    let dst = parse_algebraic_notation(&b'a',&b'8').unwrap();
+    let src = parse_algebraic_notation(&b'b', &b'7').unwrap();
+    let dst = parse_algebraic_notation(&b'a', &b'8').unwrap();
     let prompt_to = Some(PieceType::Knight);
-    let m = ChessMove{
+    let m = ChessMove {
         piece_source: src,
         piece_dest: dst,
         prompted: prompt_to,
+        dropped: None,
     };
     let mr = board.handle_move(&m);
-
+    assert!(mr.is_ok());
 }
 
 #[test]
 fn same_owner_test() {
     let board = BoardManager::new();
-    let test_data = 
-    [
+    let test_data = [
         ((b'a', b'1'), (b'a', b'2'), true),  // white to white
         ((b'a', b'8'), (b'a', b'7'), true),  // black to black
         ((b'a', b'2'), (b'a', b'3'), false), // white to empty
@@ -52,7 +63,7 @@ fn same_owner_test() {
 fn algebraic_notation_test() {
     use chess::parse::*;
     let (num_arr, char_arr) = generate_algebraic_notation_arrays();
-    for (n, c) in num_arr.into_iter().zip(char_arr.into_iter()) {
+    for (n, c) in num_arr.into_iter().zip(char_arr) {
         let result = parse_algebraic_notation(&(n as u8), &(c as u8));
         assert!(result.is_err());
         let result = parse_algebraic_notation(&(c as u8), &(n as u8));
@@ -174,6 +185,7 @@ pub fn move_validation_helper(
             Rook => is_valid_rook_move(&source_position, &dest_position),
             Pawn => is_valid_pawn_move(&source_position, &dest_position),
             Knight => is_valid_knight_move(&source_position, &dest_position),
+            _ => is_valid_move(&p_type, &source_position, &dest_position),
         };
         assert_eq!(validation_result, bv);
     }
@@ -190,4 +202,1886 @@ fn test_rook_moves() {
     let invalid_destinations = [(b'b', b'1'), (b'h', b'1'), (b'a', b'8'), (b'h', b'8')];
 
     move_validation_helper(&invalid_destinations, PieceType::Rook, src_position, false);
-}
\ No newline at end of file
+}
+
+fn play_moves(board: &mut BoardManager, moves: &[&str]) {
+    for chess_move in moves {
+        let chess_move = parse_chess_move(chess_move).unwrap();
+        board.handle_move(&chess_move).unwrap();
+    }
+}
+
+#[test]
+fn side_to_move_and_pawn_direction() {
+    let mut board = BoardManager::new();
+    assert!(board
+        .handle_move(&parse_chess_move("e7e5").unwrap())
+        .is_err());
+    assert!(board
+        .handle_move(&parse_chess_move("e2e1").unwrap())
+        .is_err());
+    play_moves(&mut board, &["e2e4", "e7e5"]);
+    // pawns can't eat straight ahead
+    assert!(board
+        .handle_move(&parse_chess_move("e4e5").unwrap())
+        .is_err());
+    assert_eq!(board.side_to_move(), Color::White);
+    assert_eq!(board.legal_moves().len(), 29);
+}
+
+#[test]
+fn fools_mate_is_checkmate() {
+    let mut board = BoardManager::new();
+    play_moves(&mut board, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert_eq!(
+        board.game_status(),
+        GameStatus::Checkmate {
+            winner: Color::Black
+        }
+    );
+    assert!(board.legal_moves().is_empty());
+}
+
+#[test]
+fn castling_moves_king_and_rook() {
+    let mut board = BoardManager::new_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w");
+    play_moves(&mut board, &["e1g1", "e8a8"]);
+    assert_eq!(board.to_fen(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+
+    // the rook on h1 moved, so castling is no longer allowed
+    let mut board = BoardManager::new_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w");
+    play_moves(&mut board, &["h1h2", "a8a7", "h2h1", "a7a8"]);
+    assert!(board
+        .handle_move(&parse_chess_move("e1h1").unwrap())
+        .is_err());
+
+    // the king can't pass through an attacked square
+    let mut board = BoardManager::new_from_fen("4kr2/8/8/8/8/8/8/R3K2R w");
+    assert!(board
+        .handle_move(&parse_chess_move("e1h1").unwrap())
+        .is_err());
+    assert!(board
+        .handle_move(&parse_chess_move("e1a1").unwrap())
+        .is_ok());
+}
+
+#[test]
+fn fen_round_trip() {
+    let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w - - 0 1";
+    assert_eq!(BoardManager::new_from_fen(fen).to_fen(), fen);
+    let fen_error = |fen| BoardManager::try_from_fen(fen).err();
+    assert_eq!(
+        fen_error("rnbqkbnr/pppppppp/8/8"),
+        Some(FenError::WrongRowCount)
+    );
+    assert_eq!(
+        fen_error("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR"),
+        Some(FenError::RowTooLong)
+    );
+    assert_eq!(
+        fen_error("rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR"),
+        Some(FenError::InvalidPiece('x'))
+    );
+    assert_eq!(
+        fen_error("rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"),
+        Some(FenError::MissingKing(Color::Black))
+    );
+    assert_eq!(
+        fen_error("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx"),
+        Some(FenError::InvalidCastlingRights)
+    );
+    // the en passant square must be the one a pawn of the side that just moved skipped
+    let after_e4 = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+    assert_eq!(BoardManager::new_from_fen(after_e4).to_fen(), after_e4);
+    for en_passant in ["e9", "j10", "e5", "e6"] {
+        assert_eq!(
+            BoardManager::try_from_fen(&after_e4.replace("e3", en_passant)).err(),
+            Some(FenError::InvalidEnPassant),
+            "{}",
+            en_passant
+        );
+    }
+    assert!(fen_error("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").is_none());
+    assert!(fen_error("6k1/8/8/8/8/8/8/8/8/6K1 w - d8 0 1").is_none());
+    assert_eq!(
+        fen_error("k4/5/5/5/4K b - c2 0 1"),
+        Some(FenError::InvalidEnPassant)
+    );
+}
+
+#[test]
+fn parse_commands() {
+    assert!(matches!(parse_command("undo"), Ok(Command::Undo)));
+    assert!(matches!(parse_command(" e2e4 "), Ok(Command::Move(_))));
+    assert!(matches!(
+        parse_command("moves e2"),
+        Ok(Command::Moves(Some(_)))
+    ));
+    assert!(parse_command("load").is_err());
+    assert!(parse_command("castle").is_err());
+    assert!(parse_command("e11e4").is_err());
+}
+
+#[test]
+fn session_undo_redo_and_resign() {
+    let mut session = GameSession::new();
+    for input in ["e2e4", "e7e5", "undo", "undo", "redo"] {
+        session.execute(parse_command(input).unwrap()).unwrap();
+    }
+    assert_eq!(
+        session.board().to_fen(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+    assert!(session.execute(Command::Redo).is_ok());
+    assert!(session.execute(Command::Redo).is_err());
+
+    session.execute(Command::Resign).unwrap();
+    assert_eq!(
+        session.result(),
+        Some(GameResult::Resignation {
+            winner: Color::Black
+        })
+    );
+    assert!(session.execute(parse_command("d2d4").unwrap()).is_err());
+}
+
+#[test]
+fn en_passant_capture() {
+    let mut board = BoardManager::new();
+    play_moves(&mut board, &["e2e4", "a7a6", "e4e5", "d7d5"]);
+    assert_eq!(
+        board.to_fen(),
+        "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"
+    );
+    play_moves(&mut board, &["e5d6"]);
+    assert_eq!(
+        board.to_fen(),
+        "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+    );
+
+    // the en passant right is lost after one move
+    let mut board = BoardManager::new();
+    play_moves(
+        &mut board,
+        &["e2e4", "a7a6", "e4e5", "d7d5", "h2h3", "h7h6"],
+    );
+    assert!(board
+        .handle_move(&parse_chess_move("e5d6").unwrap())
+        .is_err());
+}
+
+#[test]
+fn undo_redo_restores_everything() {
+    let start_fen = "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 5 20";
+    let mut board = BoardManager::new_from_fen(start_fen);
+    let moves = ["e5d6", "e8g8", "b7a8q", "g8g7", "e1a1"];
+    let mut fens = vec![board.to_fen()];
+    for chess_move in moves {
+        play_moves(&mut board, &[chess_move]);
+        fens.push(board.to_fen());
+    }
+    assert_eq!(board.history().len(), moves.len());
+
+    for fen in fens.iter().rev().skip(1) {
+        board.undo().unwrap();
+        assert_eq!(&board.to_fen(), fen);
+    }
+    assert!(board.undo().is_err());
+    assert_eq!(board.to_fen(), start_fen);
+
+    for fen in fens.iter().skip(1) {
+        board.redo().unwrap();
+        assert_eq!(&board.to_fen(), fen);
+    }
+    assert!(board.redo().is_err());
+
+    // a new move clears the moves that can be redone
+    board.undo().unwrap();
+    play_moves(&mut board, &["e1d2"]);
+    assert!(board.redo().is_err());
+    assert_eq!(
+        board.history().back().unwrap().chess_move.to_string(),
+        "e1d2"
+    );
+}
+
+#[test]
+fn move_errors_are_typed() {
+    let move_error = |fen: &str, chess_move: &str| {
+        let mut board = BoardManager::new_from_fen(fen);
+        board
+            .handle_move(&parse_chess_move(chess_move).unwrap())
+            .err()
+    };
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+    assert_eq!(move_error(start, "e3e4"), Some(MoveError::EmptySource));
+    assert_eq!(move_error(start, "e7e5"), Some(MoveError::WrongSide));
+    assert_eq!(move_error(start, "a1a2"), Some(MoveError::OwnPieceCapture));
+    assert_eq!(move_error(start, "b1b3"), Some(MoveError::IllegalPattern));
+    assert_eq!(move_error(start, "a1a5"), Some(MoveError::PathBlocked));
+    assert_eq!(move_error(start, "e2d3"), Some(MoveError::IllegalPawnMove));
+    assert_eq!(move_error(start, "e2e4q"), Some(MoveError::BadPromotion));
+    assert_eq!(
+        move_error("k7/4P3/8/8/8/8/8/4K3", "e7e8"),
+        Some(MoveError::MissingPromotion)
+    );
+    assert_eq!(
+        move_error("4k3/8/8/8/8/8/3r4/R3K3 w Q", "e1a1"),
+        Some(MoveError::CastlingThroughCheck)
+    );
+    assert_eq!(
+        move_error("4k3/8/8/8/8/8/8/RN2K3 w Q", "e1a1"),
+        Some(MoveError::CastlingBlocked)
+    );
+    assert_eq!(
+        move_error("4k3/8/8/8/8/8/8/R3K3 w -", "e1a1"),
+        Some(MoveError::CastlingNotAllowed)
+    );
+    assert_eq!(
+        move_error("4k3/4r3/8/8/8/8/4B3/4K3", "e2d3"),
+        Some(MoveError::LeavesKingInCheck)
+    );
+    assert_eq!(
+        BoardManager::new().undo().err(),
+        Some(MoveError::NothingToUndo)
+    );
+
+    assert_eq!(
+        parse_chess_move("e2e4x").err(),
+        Some(ParseError::InvalidPromotion('x'))
+    );
+    assert_eq!(
+        parse_chess_move("e2").err(),
+        Some(ParseError::InvalidMoveLength)
+    );
+    assert_eq!(
+        parse_chess_move("k2e4").err(),
+        Some(ParseError::InvalidSquare)
+    );
+    assert_eq!(parse_square("é4").err(), Some(ParseError::InvalidSquare));
+    assert_eq!(parse_square("a1é").err(), Some(ParseError::InvalidSquare));
+    assert!(BoardManager::try_from_fen("4k3/8/8/8/8/8/8/4K3 w - é6 0 1").is_err());
+    assert!(parse_command("moves é4").is_err());
+    assert!(parse_command("diagram x.svg é").is_err());
+}
+
+#[test]
+fn legal_move_generation_counts() {
+    assert_eq!(perft(&mut BoardManager::new(), 3), 8902);
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(perft(&mut BoardManager::new_from_fen(kiwipete), 2), 2039);
+    let en_passant_pins = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    assert_eq!(
+        perft(&mut BoardManager::new_from_fen(en_passant_pins), 3),
+        2812
+    );
+}
+
+#[test]
+fn attack_maps_checkers_and_pins() {
+    let board = BoardManager::new_from_fen("4k3/8/8/8/1b6/8/3NQ3/r3K3 w - - 0 1");
+    let square = |name: &str| parse_square(name).unwrap();
+
+    assert_eq!(board.checkers(), vec![square("a1")]);
+    assert_eq!(
+        board.attackers_of(&square("e1"), Color::Black),
+        vec![square("a1")]
+    );
+    // the king and the queen both defend the knight
+    assert_eq!(
+        board.attackers_of(&square("d2"), Color::White),
+        vec![square("e1"), square("e2")]
+    );
+    assert!(board.is_square_attacked(&square("c3"), Color::Black));
+    assert!(!board.is_square_attacked(&square("h8"), Color::White));
+
+    assert_eq!(
+        board.pinned_pieces(Color::White),
+        vec![Pin {
+            pinned: square("d2"),
+            pinner: square("b4"),
+            ray: vec![square("d2"), square("c3"), square("b4")],
+        }]
+    );
+}
+
+#[test]
+fn chess960_start_positions() {
+    assert_eq!(
+        chess960::start_fen(chess960::STANDARD_POSITION_INDEX).unwrap(),
+        BoardManager::new().to_fen()
+    );
+    assert_eq!(
+        chess960::start_fen(0).unwrap(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+    );
+    assert!(chess960::start_fen(chess960::POSITION_COUNT).is_none());
+
+    // every position has the bishops on different colors and the king between the rooks
+    for index in 0..chess960::POSITION_COUNT {
+        let back_row = chess960::back_row(index).unwrap();
+        let columns_of = |p_type| {
+            (0..8)
+                .filter(|column| back_row[*column] == p_type)
+                .collect::<Vec<usize>>()
+        };
+        let bishops = columns_of(PieceType::Bishop);
+        let rooks = columns_of(PieceType::Rook);
+        let king = columns_of(PieceType::King)[0];
+        assert_ne!(bishops[0] % 2, bishops[1] % 2);
+        assert!(rooks[0] < king && king < rooks[1]);
+    }
+    assert!(BoardManager::new_chess960(chess960::random_index()).is_some());
+}
+
+#[test]
+fn chess960_castling_and_fen() {
+    let mut board = BoardManager::new_from_fen("1r2k1r1/6p1/8/8/8/8/6P1/1R2K1R1 w GBgb - 0 1");
+    assert!(board.is_chess960());
+    assert_eq!(
+        board.to_fen(),
+        "1r2k1r1/6p1/8/8/8/8/6P1/1R2K1R1 w KQkq - 0 1"
+    );
+    assert_eq!(
+        board.to_shredder_fen(),
+        "1r2k1r1/6p1/8/8/8/8/6P1/1R2K1R1 w GBgb - 0 1"
+    );
+
+    play_moves(&mut board, &["e1g1", "e8b8"]);
+    assert_eq!(board.to_fen(), "2kr2r1/6p1/8/8/8/8/6P1/1R3RK1 w - - 2 2");
+    board.undo().unwrap();
+    board.undo().unwrap();
+    assert_eq!(
+        board.to_shredder_fen(),
+        "1r2k1r1/6p1/8/8/8/8/6P1/1R2K1R1 w GBgb - 0 1"
+    );
+
+    // X-FEN names the rook by its column when it isn't the outermost one
+    let board = BoardManager::new_from_fen("4k3/8/8/8/8/8/8/RR2K3 w B - 0 1");
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/RR2K3 w B - 0 1");
+    let board = BoardManager::new_from_fen("4k3/8/8/8/8/8/8/RR2K3 w Q - 0 1");
+    assert_eq!(board.to_shredder_fen(), "4k3/8/8/8/8/8/8/RR2K3 w A - 0 1");
+    assert_eq!(
+        BoardManager::try_from_fen("4k3/8/8/8/8/8/8/RR2K3 w C - 0 1").err(),
+        Some(FenError::InvalidCastlingRights)
+    );
+    assert!(FenError::InvalidCastlingRights
+        .to_string()
+        .contains("The Column Letters Of The Rooks"));
+}
+
+#[test]
+fn uci_front_end() {
+    let mut uci_engine = UciEngine::new();
+    let reply = uci_engine.handle_line("uci");
+    assert!(reply.contains(&"option name UCI_Chess960 type check default false".to_string()));
+    assert_eq!(reply.last().unwrap(), "uciok");
+    assert_eq!(uci_engine.handle_line("isready"), vec!["readyok"]);
+
+    uci_engine.handle_line("position fen r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 moves e1g1");
+    assert_eq!(
+        uci_engine.board().to_fen(),
+        "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1"
+    );
+    // standard castling is written as the king moving two squares
+    let board = BoardManager::new_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_eq!(
+        board.move_to_uci(&parse_chess_move("e1h1").unwrap()),
+        "e1g1"
+    );
+    let reply = uci_engine.handle_line("go depth 1");
+    assert!(reply.len() == 1 && reply[0].starts_with("bestmove "));
+
+    uci_engine.handle_line("setoption name UCI_Chess960 value true");
+    uci_engine.handle_line("position fen 1r2k1r1/6p1/8/8/8/8/6P1/1R2K1R1 w GBgb - 0 1 moves e1g1");
+    assert_eq!(
+        uci_engine.board().to_shredder_fen(),
+        "1r2k1r1/6p1/8/8/8/8/6P1/1R3RK1 b gb - 1 1"
+    );
+
+    uci_engine.handle_line("quit");
+    assert!(!uci_engine.is_running());
+}
+
+#[test]
+fn three_check_counts_checks_and_wins() {
+    let mut board = BoardManager::new();
+    board.set_variant(VariantKind::ThreeCheck);
+    play_moves(
+        &mut board,
+        &["e2e4", "f7f6", "d1h5", "g7g6", "h5g6", "h7g6"],
+    );
+    assert_eq!(board.checks_given().white, 2);
+    assert_eq!(
+        board.to_fen(),
+        "rnbqkbnr/ppppp3/5pp1/8/4P3/8/PPPP1PPP/RNB1KBNR w KQkq - 0 4 +2+0"
+    );
+
+    // the fen suffix brings back the counters and the variant
+    let mut board = BoardManager::new_from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0");
+    assert_eq!(board.variant().name(), "threecheck");
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+    play_moves(&mut board, &["a1a8"]);
+    assert_eq!(
+        board.game_status(),
+        GameStatus::ThirdCheck {
+            winner: Color::White
+        }
+    );
+    assert!(board.legal_moves().is_empty());
+    board.undo().unwrap();
+    assert_eq!(board.checks_given().white, 2);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0");
+
+    // the engine goes for the third check
+    let chess_move = engine::best_move(&mut board, 2).unwrap();
+    board.handle_move(&chess_move).unwrap();
+    assert_eq!(board.variant_winner(), Some(Color::White));
+
+    assert_eq!(
+        BoardManager::try_from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +4+0").err(),
+        Some(FenError::InvalidCheckCounters)
+    );
+}
+
+#[test]
+fn session_mode_menu() {
+    let mut session = GameSession::new();
+    assert!(session
+        .execute(parse_command("mode").unwrap())
+        .unwrap()
+        .contains("threecheck"));
+    assert!(parse_command("mode chaturanga").is_err());
+
+    session
+        .execute(parse_command("mode 3check").unwrap())
+        .unwrap();
+    assert_eq!(session.board().variant().name(), "threecheck");
+    session.execute(parse_command("new").unwrap()).unwrap();
+    assert_eq!(session.board().variant().name(), "threecheck");
+
+    session
+        .execute(parse_command("load 4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0").unwrap())
+        .unwrap();
+    let output = session.execute(parse_command("a1a8").unwrap()).unwrap();
+    assert!(output.contains("Checks Given: White 3/3"));
+    assert_eq!(
+        session.result(),
+        Some(GameResult::ThirdCheck {
+            winner: Color::White
+        })
+    );
+}
+
+#[test]
+fn king_of_the_hill() {
+    use cte::chess::board_manager::BoardSizeInfo;
+    use cte::chess::variant::{distance_to_hill, is_hill_square};
+
+    let mut board = BoardManager::new_from_fen("4k3/8/8/8/8/3K4/8/8 w - - 0 1");
+    board.set_variant(VariantKind::KingOfTheHill);
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+
+    // the engine walks the king onto the hill
+    let chess_move = engine::best_move(&mut board, 1).unwrap();
+    board.handle_move(&chess_move).unwrap();
+    assert_eq!(
+        board.game_status(),
+        GameStatus::KingOfTheHill {
+            winner: Color::White
+        }
+    );
+    assert!(board.legal_moves().is_empty());
+    board.undo().unwrap();
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+
+    // the hill is only the four center squares
+    play_moves(&mut board, &["d3c4"]);
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+
+    // on odd sizes the hill is the single middle square, at the same distance from every side
+    let size = BoardSizeInfo::new(5, 5).unwrap();
+    let hill: Vec<Position> = (0..5)
+        .flat_map(|row| (0..5).map(move |column| Position::new(row, column)))
+        .filter(|position| is_hill_square(position, &size))
+        .collect();
+    assert_eq!(hill, vec![parse_square("c3").unwrap()]);
+    for corner in ["a1", "a5", "e1", "e5"] {
+        assert_eq!(distance_to_hill(&parse_square(corner).unwrap(), &size), 2);
+    }
+    let size = BoardSizeInfo::new(8, 8).unwrap();
+    assert_eq!(distance_to_hill(&parse_square("a1").unwrap(), &size), 3);
+    assert_eq!(distance_to_hill(&parse_square("h8").unwrap(), &size), 3);
+
+    let mut session = GameSession::new();
+    session
+        .execute(parse_command("mode koth").unwrap())
+        .unwrap();
+    for chess_move in ["e2e3", "e7e6", "e1e2", "e8e7", "e2d3", "e7d6", "d3d4"] {
+        session.execute(parse_command(chess_move).unwrap()).unwrap();
+    }
+    assert_eq!(
+        session.result(),
+        Some(GameResult::KingOfTheHill {
+            winner: Color::White
+        })
+    );
+}
+
+#[test]
+fn atomic_explosions() {
+    use cte::chess::piece::Piece;
+    use cte::cli::render::captures_text;
+
+    let mut board = BoardManager::new_from_fen("4k3/8/2n1b3/3q4/8/8/3Q4/4K3 w - - 0 1");
+    board.set_variant(VariantKind::Atomic);
+    play_moves(&mut board, &["d2d5"]);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    // the exploded pieces count as captured, the own queen doesn't count for black
+    assert_eq!(
+        board.captured(Color::White),
+        vec![
+            Piece::new(PieceType::Knight, Color::Black),
+            Piece::new(PieceType::Bishop, Color::Black),
+            Piece::new(PieceType::Queen, Color::Black)
+        ]
+    );
+    assert!(board.captured(Color::Black).is_empty());
+    assert_eq!(board.material().advantage(), None);
+    assert_eq!(captures_text(&board, Color::White), "♞♝♛");
+    let chess_turn = board.undo().unwrap();
+    assert_eq!(chess_turn.exploded.iter().flatten().count(), 3);
+    assert_eq!(board.to_fen(), "4k3/8/2n1b3/3q4/8/8/3Q4/4K3 w - - 0 1");
+
+    let mut board = BoardManager::new_from_fen("4k3/8/8/8/8/8/3q4/3QK3 w - - 0 1");
+    board.set_variant(VariantKind::Atomic);
+    let capture = |chess_move| parse_chess_move(chess_move).unwrap();
+    assert_eq!(
+        board.handle_move(&capture("e1d2")),
+        Err(MoveError::KingCapture)
+    );
+    assert_eq!(
+        board.handle_move(&capture("d1d2")),
+        Err(MoveError::ExplodesOwnKing)
+    );
+
+    // the kings next to each other can't give check
+    let board = BoardManager::new_from_fen("8/8/8/8/8/3k4/3K3r/8 w - - 0 1");
+    assert!(board.is_check(Color::White));
+    let mut board = board.clone();
+    board.set_variant(VariantKind::Atomic);
+    assert!(!board.is_check(Color::White));
+
+    // pawns survive the explosion, the king next to the capture doesn't
+    let mut board = BoardManager::new_from_fen("4k3/3pp3/8/8/8/8/8/3RK3 w - - 0 1");
+    board.set_variant(VariantKind::Atomic);
+    play_moves(&mut board, &["d1d7"]);
+    assert_eq!(
+        board.game_status(),
+        GameStatus::KingExploded {
+            winner: Color::White
+        }
+    );
+    assert!(!board.has_king(Color::Black));
+    assert_eq!(
+        board.piece_at(&parse_square("e7").unwrap()).unwrap().p_type,
+        PieceType::Pawn
+    );
+
+    let mut board = BoardManager::new();
+    board.set_variant(VariantKind::Atomic);
+    assert_eq!(perft(&mut board, 3), 8902);
+}
+
+#[test]
+fn crazyhouse_pockets_and_drops() {
+    let mut board = BoardManager::new();
+    board.set_variant(VariantKind::Crazyhouse);
+    play_moves(
+        &mut board,
+        &["e2e4", "d7d5", "e4d5", "d8d5", "b1c3", "d5a5"],
+    );
+    assert_eq!(
+        board.to_fen(),
+        "rnb1kbnr/ppp1pppp/8/q7/8/2N5/PPPP1PPP/R1BQKBNR[Pp] w KQkq - 2 4"
+    );
+    play_moves(&mut board, &["P@d5"]);
+    assert_eq!(board.pockets().white.count(PieceType::Pawn), 0);
+    board.undo().unwrap();
+    assert_eq!(board.pockets().white.count(PieceType::Pawn), 1);
+
+    let drop = |chess_move| parse_chess_move(chess_move).unwrap();
+    assert_eq!(drop("N@f3").to_string(), "N@f3");
+    assert_eq!(
+        parse_chess_move("K@e4").err(),
+        Some(ParseError::InvalidDrop('k'))
+    );
+    assert_eq!(
+        board.handle_move(&drop("P@e8")),
+        Err(MoveError::DropOnOccupied)
+    );
+    assert_eq!(
+        board.handle_move(&drop("P@d8")),
+        Err(MoveError::PawnDropOnBackRank)
+    );
+    assert_eq!(
+        board.handle_move(&drop("Q@d4")),
+        Err(MoveError::NotInPocket)
+    );
+    assert_eq!(
+        BoardManager::new().handle_move(&drop("P@d4")),
+        Err(MoveError::DropNotAllowed)
+    );
+
+    // dropping a piece between the rook and the king answers the check
+    let mut board = BoardManager::new_from_fen("4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1");
+    assert_eq!(board.variant().name(), "crazyhouse");
+    let legal_moves: Vec<String> = board.legal_moves().iter().map(|m| m.to_string()).collect();
+    assert_eq!(legal_moves.len(), 6);
+    assert!(legal_moves.contains(&"N@c1".to_string()));
+}
+
+#[test]
+fn crazyhouse_promoted_pieces_return_as_pawns() {
+    let mut board = BoardManager::new_from_fen("4k3/1P6/8/8/8/8/8/4K3[] w - - 0 1");
+    play_moves(&mut board, &["b7b8q"]);
+    assert_eq!(board.to_fen(), "1Q~2k3/8/8/8/8/8/8/4K3[] b - - 0 1");
+    board.undo().unwrap();
+    assert_eq!(board.to_fen(), "4k3/1P6/8/8/8/8/8/4K3[] w - - 0 1");
+
+    let mut board = BoardManager::new_from_fen("r3k3/8/8/8/8/8/8/Q~3K3[] b - - 0 1");
+    play_moves(&mut board, &["a8a1"]);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/r3K3[p] w - - 0 2");
+    board.undo().unwrap();
+    assert_eq!(board.to_fen(), "r3k3/8/8/8/8/8/8/Q~3K3[] b - - 0 1");
+
+    assert_eq!(
+        BoardManager::try_from_fen("4k3/8/8/8/8/8/8/4K3[Kq] w - - 0 1").err(),
+        Some(FenError::InvalidPocket)
+    );
+}
+
+#[test]
+fn antichess_rules() {
+    let mut board = BoardManager::new();
+    board.set_variant(VariantKind::Antichess);
+    assert!(board.castling_rights().is_empty());
+    play_moves(&mut board, &["e2e3", "b7b5"]);
+    // the bishop must take the pawn
+    assert_eq!(
+        board.handle_move(&parse_chess_move("a2a3").unwrap()),
+        Err(MoveError::CaptureRequired)
+    );
+    let legal_moves: Vec<String> = board.legal_moves().iter().map(|m| m.to_string()).collect();
+    assert_eq!(legal_moves, vec!["f1b5"]);
+    // the moves of a piece are forced to capture too
+    let mut board = BoardManager::new_variant(VariantKind::Antichess);
+    play_moves(&mut board, &["e2e4", "d7d5"]);
+    assert!(board.legal_moves_from(&Position::new(0, 6)).is_empty());
+    let legal_moves: Vec<String> = board
+        .legal_moves_from(&Position::new(3, 4))
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+    assert_eq!(legal_moves, vec!["e4d5"]);
+    let mut session = GameSession::from_board(board);
+    assert_eq!(
+        session.execute(parse_command("moves g1").unwrap()).unwrap(),
+        "No Legal Moves"
+    );
+
+    // the king is an ordinary piece, it can be missing, attacked and promoted to
+    assert_eq!(
+        BoardManager::try_from_fen("8/P7/8/8/8/8/8/k7 w - - 0 1").err(),
+        Some(FenError::MissingKing(Color::White))
+    );
+    let mut board =
+        BoardManager::try_from_variant_fen("8/P7/8/8/8/8/8/k7 w - - 0 1", VariantKind::Antichess)
+            .unwrap();
+    assert!(board.checkers().is_empty());
+    assert!(board.pinned_pieces(Color::White).is_empty());
+    play_moves(&mut board, &["a7a8k", "a1b2"]);
+    let mut board = BoardManager::new_from_fen("7k/P7/8/8/8/8/8/K7 w - - 0 1");
+    assert_eq!(
+        board.handle_move(&parse_chess_move("a7a8k").unwrap()),
+        Err(MoveError::BadPromotion)
+    );
+
+    // losing every piece wins
+    let mut board =
+        BoardManager::try_from_variant_fen("8/8/8/8/8/8/1p6/R7 b - - 0 1", VariantKind::Antichess)
+            .unwrap();
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+    play_moves(&mut board, &["b2a1q"]);
+    assert_eq!(
+        board.game_status(),
+        GameStatus::OutOfMoves {
+            winner: Color::White
+        }
+    );
+
+    let mut board = BoardManager::new();
+    board.set_variant(VariantKind::Antichess);
+    assert_eq!(perft(&mut board, 3), 8067);
+}
+
+#[test]
+fn horde_rules() {
+    let mut board = BoardManager::new_variant(VariantKind::Horde);
+    assert_eq!(board.to_fen(), VariantKind::Horde.start_fen());
+    assert_eq!(perft(&mut board, 3), 1274);
+
+    // the white king is missing but the black one is still required
+    assert_eq!(
+        BoardManager::try_from_variant_fen("8/8/8/8/8/8/8/P7 w - - 0 1", VariantKind::Horde).err(),
+        Some(FenError::MissingKing(Color::Black))
+    );
+
+    // the pawns on the first row can step twice, without giving en passant
+    let mut board =
+        BoardManager::try_from_variant_fen("7k/8/8/8/8/8/8/P7 w - - 0 1", VariantKind::Horde)
+            .unwrap();
+    play_moves(&mut board, &["a1a3"]);
+    assert_eq!(board.to_fen(), "7k/8/8/8/8/P7/8/8 b - - 0 1");
+    let mut board = BoardManager::new_from_fen("7k/8/8/8/8/8/8/P6K w - - 0 1");
+    assert_eq!(
+        board.handle_move(&parse_chess_move("a1a3").unwrap()),
+        Err(MoveError::IllegalPattern)
+    );
+
+    // capturing every white piece wins for black
+    let mut board =
+        BoardManager::try_from_variant_fen("7k/8/8/8/8/8/1q6/P7 b - - 0 1", VariantKind::Horde)
+            .unwrap();
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+    play_moves(&mut board, &["b2a1"]);
+    assert_eq!(
+        board.game_status(),
+        GameStatus::AllPiecesCaptured {
+            winner: Color::Black
+        }
+    );
+}
+
+#[test]
+fn racing_kings_rules() {
+    let mut board = BoardManager::new_variant(VariantKind::RacingKings);
+    assert!(board.castling_rights().is_empty());
+    assert_eq!(perft(&mut board, 3), 11264);
+
+    // no move may give check
+    let mut board = BoardManager::try_from_variant_fen(
+        "k7/8/8/8/8/8/8/K6R w - - 0 1",
+        VariantKind::RacingKings,
+    )
+    .unwrap();
+    assert_eq!(
+        board.handle_move(&parse_chess_move("h1h8").unwrap()),
+        Err(MoveError::GivesCheck)
+    );
+
+    // black gets one more move to reach the last row too
+    let race = "8/1k4K1/8/8/8/8/8/8 w - - 0 1";
+    let mut board = BoardManager::try_from_variant_fen(race, VariantKind::RacingKings).unwrap();
+    play_moves(&mut board, &["g7g8"]);
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+    play_moves(&mut board, &["b7b8"]);
+    assert_eq!(board.game_status(), GameStatus::RaceDrawn);
+
+    let mut board = BoardManager::try_from_variant_fen(race, VariantKind::RacingKings).unwrap();
+    play_moves(&mut board, &["g7g8", "b7c7"]);
+    assert_eq!(
+        board.game_status(),
+        GameStatus::GoalReached {
+            winner: Color::White
+        }
+    );
+
+    let mut session = GameSession::from_board(BoardManager::new());
+    session
+        .execute(parse_command("mode racingkings").unwrap())
+        .unwrap();
+    assert_eq!(
+        session.board().to_fen(),
+        VariantKind::RacingKings.start_fen()
+    );
+    assert!(session.execute(parse_command("new 960").unwrap()).is_err());
+}
+
+/// a house rule: pawns promote only to knights and the queens never move
+#[derive(Debug)]
+struct KnightsAndStillQueens;
+
+impl Variant for KnightsAndStillQueens {
+    fn name(&self) -> &str {
+        "knights"
+    }
+
+    fn can_promote_to(&self, p_type: PieceType) -> bool {
+        p_type == PieceType::Knight
+    }
+
+    fn filter_moves(&self, board: &BoardManager, legal_moves: &mut Vec<ChessMove>) {
+        legal_moves.retain(|chess_move| {
+            board
+                .piece_at(&chess_move.piece_source)
+                .map(|piece| piece.p_type)
+                != Some(PieceType::Queen)
+        });
+    }
+}
+
+#[test]
+fn house_rules_variant() {
+    let house_rules: Rc<dyn Variant> = Rc::new(KnightsAndStillQueens);
+    let mut board = BoardManager::new_variant(Rc::clone(&house_rules));
+    assert_eq!(board.variant().name(), "knights");
+    play_moves(&mut board, &["e2e4", "e7e5"]);
+    let legal_moves: Vec<String> = board.legal_moves().iter().map(|m| m.to_string()).collect();
+    assert!(!legal_moves.iter().any(|m| m.starts_with("d1")));
+
+    let mut board =
+        BoardManager::try_from_variant_fen("7k/P7/8/8/8/8/8/K7 w - - 0 1", Rc::clone(&house_rules))
+            .unwrap();
+    assert_eq!(
+        board
+            .legal_moves()
+            .iter()
+            .filter(|m| m.prompted.is_some())
+            .count(),
+        1
+    );
+    assert_eq!(
+        board.handle_move(&parse_chess_move("a7a8q").unwrap()),
+        Err(MoveError::BadPromotion)
+    );
+    play_moves(&mut board, &["a7a8n"]);
+    assert!(engine::best_move(&mut board, 2).is_some());
+
+    // the pocket and the check counters don't replace the variant asked for
+    assert_eq!(
+        BoardManager::try_from_variant_fen(
+            "4k3/8/8/8/8/8/8/4K3[Q] w - - 0 1",
+            Rc::clone(&house_rules)
+        )
+        .err(),
+        Some(FenError::UnexpectedPocket)
+    );
+    assert_eq!(
+        BoardManager::try_from_variant_fen(
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1 +1+0",
+            Rc::clone(&house_rules)
+        )
+        .err(),
+        Some(FenError::UnexpectedCheckCounters)
+    );
+    assert_eq!(
+        BoardManager::try_from_variant_fen(
+            "4k3/8/8/8/8/8/8/4K3[Q] w - - 0 1",
+            VariantKind::KingOfTheHill
+        )
+        .err(),
+        Some(FenError::UnexpectedPocket)
+    );
+    let board =
+        BoardManager::try_from_variant_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1", house_rules).unwrap();
+    assert_eq!(board.variant().name(), "knights");
+}
+
+#[test]
+fn board_sizes() {
+    use cte::chess::board_manager::BoardSizeInfo;
+
+    // Gardner minichess, the pawns can't step twice
+    let gardner = BoardSizeInfo::from_name("5x5").unwrap();
+    let mut board = BoardManager::new_sized(gardner);
+    assert_eq!(board.to_fen(), "rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1");
+    assert_eq!(perft(&mut board, 2), 53);
+    assert_eq!(
+        board.handle_move(&parse_chess_move("a2a4").unwrap()),
+        Err(MoveError::IllegalPattern)
+    );
+    assert_eq!(
+        board.handle_move(&parse_chess_move("a2a6").unwrap()),
+        Err(MoveError::OutsideBoard)
+    );
+    assert!(board.to_string().contains("a b c d e\n"));
+
+    assert_eq!(BoardSizeInfo::from_name("11x8"), None);
+    assert_eq!(BoardSizeInfo::from_name("6x6").unwrap().to_string(), "6x6");
+
+    // ten columns, castling lands the king on the i or c column
+    let fen = "r4k3r/pppppppppp/10/10/10/10/PPPPPPPPPP/R4K3R w KQkq - 0 1";
+    let mut board = BoardManager::new_from_fen(fen);
+    assert_eq!(board.size().column_count(), 10);
+    assert_eq!(board.to_fen(), fen);
+    play_moves(&mut board, &["f1i1", "f8c8"]);
+    assert_eq!(
+        board.to_fen(),
+        "2kr5r/pppppppppp/10/10/10/10/PPPPPPPPPP/R6RK1 w - - 2 2"
+    );
+
+    // ten rows, the squares have two digit rows
+    let mut board = BoardManager::new_sized(BoardSizeInfo::from_name("8x10").unwrap());
+    assert_eq!(parse_square("e10").unwrap(), Position::new(9, 4));
+    play_moves(&mut board, &["e2e4", "e9e7"]);
+    assert_eq!(
+        board.handle_move(&parse_chess_move("e10e9").unwrap()),
+        Err(MoveError::WrongSide)
+    );
+    assert!(board.to_fen().starts_with("rnbqkbnr/pppp1ppp/8/4p3/"));
+}
+
+#[test]
+fn fairy_pieces() {
+    use cte::chess::board_manager::BoardSizeInfo;
+
+    assert_eq!(parse_betza("BN").unwrap().len(), 2);
+    assert_eq!(parse_betza("NN").unwrap()[0].reach, Reach::Ride);
+    assert_eq!(parse_betza("gQ").unwrap()[1].reach, Reach::Hop);
+    assert_eq!(parse_betza("NX"), Err('X'));
+    // every descriptor the pieces hand out can be parsed and gives the piece its atoms
+    for p_type in PIECE_TYPES {
+        let atoms = p_type
+            .betza()
+            .map(|descriptor| parse_betza(descriptor).unwrap());
+        assert_eq!(
+            piece_atoms(&p_type),
+            atoms.unwrap_or_default(),
+            "{:?}",
+            p_type
+        );
+    }
+    assert_eq!(PieceType::Pawn.betza(), None);
+
+    let src = parse_square("e4").unwrap();
+    move_validation_helper(
+        &[(b'f', b'6'), (b'h', b'7')],
+        PieceType::Archbishop,
+        src,
+        true,
+    );
+    move_validation_helper(
+        &[(b'e', b'5'), (b'h', b'4')],
+        PieceType::Archbishop,
+        src,
+        false,
+    );
+    move_validation_helper(
+        &[(b'e', b'8'), (b'g', b'5')],
+        PieceType::Chancellor,
+        src,
+        true,
+    );
+    move_validation_helper(&[(b'h', b'5'), (b'f', b'7')], PieceType::Camel, src, true);
+    move_validation_helper(&[(b'g', b'7'), (b'b', b'6')], PieceType::Zebra, src, true);
+    move_validation_helper(&[(b'f', b'6'), (b'h', b'5')], PieceType::Zebra, src, false);
+
+    // capablanca chess on ten columns
+    let mut board = BoardManager::new_sized(BoardSizeInfo::from_name("10x8").unwrap());
+    assert_eq!(
+        board.to_fen(),
+        "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1"
+    );
+    assert_eq!(perft(&mut board, 2), 784);
+    play_moves(&mut board, &["h1g3", "c8d6"]);
+    assert_eq!(
+        board.piece_at(&parse_square("g3").unwrap()).unwrap().p_type,
+        PieceType::Chancellor
+    );
+
+    // the seirawan letters of the hawk and the elephant, pawns promote to the pieces of the game
+    let mut board = BoardManager::new_from_fen("4k3/P7/8/8/8/8/8/H3K2E w - - 0 1");
+    assert_eq!(board.to_fen(), "4k3/P7/8/8/8/8/8/A3K2C w - - 0 1");
+    assert!(board
+        .handle_move(&parse_chess_move("a7a8c").unwrap())
+        .is_ok());
+    let mut board = BoardManager::new_from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(
+        board.handle_move(&parse_chess_move("a7a8a").unwrap()),
+        Err(MoveError::BadPromotion)
+    );
+
+    // the grasshopper lands right behind the first piece on its line
+    let mut board = BoardManager::new_from_fen("4k3/8/8/8/4g3/8/4P3/4K3 w - - 0 1");
+    assert!(board.is_check(Color::White));
+    assert_eq!(board.legal_moves().len(), 5);
+    play_moves(&mut board, &["e2e3"]);
+    assert_eq!(
+        board.handle_move(&parse_chess_move("e4e1").unwrap()),
+        Err(MoveError::PathBlocked)
+    );
+    assert!(board
+        .handle_move(&parse_chess_move("e4e2").unwrap())
+        .is_ok());
+}
+
+#[test]
+fn piece_char_conversions() {
+    use cte::chess::piece::Piece;
+
+    assert_eq!(PieceType::try_from('q'), Ok(PieceType::Queen));
+    assert_eq!(PieceType::try_from('H'), Ok(PieceType::Archbishop));
+    assert_eq!(PieceType::try_from('x'), Err(PieceCharError('x')));
+    assert_eq!(Color::try_from('n'), Ok(Color::Black));
+    assert_eq!(Color::try_from('7'), Err(PieceCharError('7')));
+    assert_eq!(
+        Piece::try_from('R'),
+        Ok(Piece::new(PieceType::Rook, Color::White))
+    );
+    assert_eq!(
+        Piece::try_from('?').unwrap_err().to_string(),
+        "'?' Is Not The Letter Of A Piece"
+    );
+
+    for p_type in PIECE_TYPES {
+        for color in [Color::White, Color::Black] {
+            let piece = Piece::new(p_type, color);
+            assert_eq!(Piece::try_from(piece.to_fen_char()), Ok(piece));
+        }
+    }
+    assert_eq!(PieceType::Knight.to_san_letter(), Some('N'));
+    assert_eq!(PieceType::Pawn.to_san_letter(), None);
+
+    assert_eq!(
+        BoardManager::try_from_fen("4k3/8/8/8/8/8/8/4K2X w - - 0 1").err(),
+        Some(FenError::InvalidPiece('X'))
+    );
+    assert_eq!(
+        parse_chess_move("e7e8x").err(),
+        Some(ParseError::InvalidPromotion('x'))
+    );
+    assert_eq!(
+        parse_chess_move("K@e4").err(),
+        Some(ParseError::InvalidDrop('k'))
+    );
+    // the fairy pieces are valid promotions and drops too
+    assert!(parse_chess_move("e7e8a").is_ok());
+    assert!(parse_chess_move("C@e4").is_ok());
+    assert_eq!(
+        ParseError::InvalidDrop('k').to_string(),
+        "'k' Is Not A Piece That Can Be Dropped, Use The Letter Of Any Piece But The King"
+    );
+}
+
+#[test]
+fn terminal_ui_keys() {
+    use cte::cli::tui::{parse_keys, Key, Tui};
+
+    assert_eq!(
+        parse_keys(b"\x1b[A\x1b[Dk\r\x1b \x03"),
+        vec![
+            Key::Up,
+            Key::Left,
+            Key::Char('k'),
+            Key::Enter,
+            Key::Escape,
+            Key::Char(' '),
+            Key::Interrupt
+        ]
+    );
+
+    let mut session = GameSession::new();
+    let mut tui = Tui::new(session.board());
+    assert_eq!(tui.cursor(), parse_square("e1").unwrap());
+    tui.handle_key(&mut session, Key::Enter);
+    assert_eq!(tui.message(), "That Piece Has No Legal Moves");
+    assert!(!tui.take_executed());
+
+    for key in parse_keys(b"\x1b[A ") {
+        assert!(tui.handle_key(&mut session, key));
+    }
+    assert_eq!(tui.selected(), Some(parse_square("e2").unwrap()));
+    assert_eq!(
+        tui.targets(),
+        vec![parse_square("e3").unwrap(), parse_square("e4").unwrap()]
+    );
+    for key in parse_keys(b"kk\r") {
+        tui.handle_key(&mut session, key);
+    }
+    assert_eq!(session.board().side_to_move(), Color::Black);
+    assert_eq!(tui.selected(), None);
+    // the move tells the caller to save the game, once
+    assert!(tui.take_executed());
+    assert!(!tui.take_executed());
+    let screen = tui.render(&session);
+    assert!(screen.contains("White Played e2e4"));
+    assert!(screen.contains("  1. e2e4"));
+    assert!(screen.contains("Black To Move"));
+
+    // under forced captures only the capturing moves are targets
+    let mut board = BoardManager::new_variant(VariantKind::Antichess);
+    play_moves(&mut board, &["e2e4", "d7d5"]);
+    let mut session = GameSession::from_board(board);
+    let mut tui = Tui::new(session.board());
+    for key in parse_keys(b"ll ") {
+        tui.handle_key(&mut session, key);
+    }
+    assert_eq!(tui.message(), "That Piece Has No Legal Moves");
+    for key in parse_keys(b"\x1bhhkkk ") {
+        tui.handle_key(&mut session, key);
+    }
+    assert_eq!(tui.targets(), vec![parse_square("d5").unwrap()]);
+
+    // the promotion piece is chosen by its letter
+    let board = BoardManager::new_from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    let mut session = GameSession::from_board(board);
+    let mut tui = Tui::new(session.board());
+    for key in parse_keys(b"hhhhkkkkkk \x1b[A ") {
+        tui.handle_key(&mut session, key);
+    }
+    assert!(tui.message().starts_with("Promote To: q r b n"));
+    tui.handle_key(&mut session, Key::Char('n'));
+    assert!(session.board().to_fen().starts_with("N3k3/8/"));
+    assert!(!tui.handle_key(&mut session, Key::Char('q')));
+}
+
+#[test]
+fn terminal_ui_mouse() {
+    use cte::cli::tui::{parse_keys, Key, Mouse, MouseAction, Tui};
+
+    assert_eq!(
+        parse_keys(b"\x1b[<0;16;8M\x1b[<32;17;7M\x1b[<0;16;6m\x1b[<64;1;1M\x1b[<2;5;5M"),
+        vec![
+            Key::Mouse(Mouse {
+                action: MouseAction::Press,
+                column: 16,
+                line: 8
+            }),
+            Key::Mouse(Mouse {
+                action: MouseAction::Drag,
+                column: 17,
+                line: 7
+            }),
+            Key::Mouse(Mouse {
+                action: MouseAction::Release,
+                column: 16,
+                line: 6
+            }),
+        ]
+    );
+
+    // dragging the e2 pawn to e4, the board turns to the side to move after it
+    let mut session = GameSession::new();
+    session
+        .execute(Command::Orientation(Some(Orientation::SideToMove)))
+        .unwrap();
+    let mut tui = Tui::new(session.board());
+    assert_eq!(
+        tui.position_at(session.board(), 16, 8),
+        parse_square("e2").ok()
+    );
+    assert_eq!(tui.position_at(session.board(), 3, 8), None);
+    for key in parse_keys(b"\x1b[<0;16;8M\x1b[<32;16;7M\x1b[<0;17;6m") {
+        tui.handle_key(&mut session, key);
+    }
+    assert_eq!(session.board().history().len(), 1);
+
+    // clicking the knight and then its destination, the first row is at the top now
+    for key in parse_keys(b"\x1b[<0;7;9M\x1b[<0;7;9m\x1b[<0;10;7M") {
+        tui.handle_key(&mut session, key);
+    }
+    assert!(session
+        .board()
+        .to_fen()
+        .starts_with("r1bqkbnr/pppppppp/2n5/"));
+
+    // the promotion popup starts over the destination square
+    let board = BoardManager::new_from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+    let mut session = GameSession::from_board(board);
+    let mut tui = Tui::new(session.board());
+    for key in parse_keys(b"\x1b[<0;7;3M\x1b[<0;7;2m") {
+        tui.handle_key(&mut session, key);
+    }
+    let popup = tui.render(&session);
+    let popup = &popup[popup.rfind("\x1b[2;7H").unwrap()..];
+    assert_eq!(
+        popup.matches(" ♕ ").count() + popup.matches(" ♘ ").count(),
+        2
+    );
+    tui.handle_key(&mut session, parse_keys(b"\x1b[<0;11;2M")[0]);
+    assert!(session.board().to_fen().starts_with("1R2k3/"));
+}
+
+#[test]
+fn board_themes() {
+    use cte::cli::render::{default_theme, render_board, Theme};
+
+    // the tests don't write to a terminal
+    assert_eq!(default_theme(), Theme::Plain);
+    assert_eq!(Theme::from_name("High-Contrast"), Some(Theme::HighContrast));
+
+    let mut board = BoardManager::new();
+    assert_eq!(
+        render_board(&board, Theme::Plain, board.orientation()),
+        board.to_string()
+    );
+    play_moves(&mut board, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    let colored = render_board(&board, Theme::Classic, board.orientation());
+    let palette = Theme::Classic.palette();
+    // the checked king and the two squares of the last move
+    assert_eq!(
+        colored.matches(&format!("\x1b[{};", palette.check)).count(),
+        1
+    );
+    assert_eq!(
+        colored
+            .matches(&format!("\x1b[{}", palette.last_move))
+            .count(),
+        2
+    );
+    assert_eq!(colored.lines().count(), 10);
+    assert!(!render_board(&board, Theme::Monochrome, board.orientation()).contains("48;5"));
+
+    let mut session = GameSession::new();
+    assert_eq!(
+        session
+            .execute(parse_command("theme").unwrap())
+            .unwrap()
+            .lines()
+            .nth(1),
+        Some(" *plain")
+    );
+    let output = session
+        .execute(parse_command("theme colorblind").unwrap())
+        .unwrap();
+    assert!(output.starts_with("Theme colorblind\n"));
+    assert!(output.contains("\x1b[48;5;74m"));
+    session.execute(parse_command("new").unwrap()).unwrap();
+    assert_eq!(session.theme(), Theme::ColorblindSafe);
+    assert!(parse_command("theme neon").is_err());
+}
+
+#[test]
+fn board_renderers() {
+    use cte::chess::renderer::Renderer;
+    use cte::{parse_game_options, GameOptions};
+
+    let mut board = BoardManager::new_from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1");
+    assert!(board.to_string().contains(" 1 ♖ · · · ♔ · · ·\n"));
+    board.set_renderer(Renderer::Letters);
+    assert!(board
+        .to_string()
+        .starts_with("   a b c d e f g h\n 8 . . . . k . . .\n"));
+    board.set_renderer(Renderer::WideGlyphs);
+    assert!(board.to_string().contains("\n 1 ♖ ·  ·  ·  ♔ ·  ·  · \n"));
+    board.set_renderer(Renderer::AsciiBoxes);
+    let boxes = board.to_string();
+    assert!(boxes.contains("\n 1 | R |   |   |   | K |   |   |   | 1\n"));
+    assert_eq!(boxes.lines().count(), 19);
+    assert!(boxes.is_ascii());
+
+    let mut session = GameSession::new();
+    assert!(session
+        .execute(parse_command("pieces letters").unwrap())
+        .unwrap()
+        .contains(" 2 P P P P P P P P\n"));
+    session.execute(parse_command("new").unwrap()).unwrap();
+    assert_eq!(session.board().renderer(), Renderer::Letters);
+    assert!(parse_command("pieces emoji").is_err());
+
+    let args = |line: &str| {
+        line.split_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(
+        parse_game_options(&args("--pieces boxes")),
+        Ok(GameOptions {
+            theme: None,
+            renderer: Some(Renderer::AsciiBoxes),
+            ..GameOptions::default()
+        })
+    );
+    assert!(parse_game_options(&args("--pieces")).is_err());
+    assert!(parse_game_options(&args("--colors red")).is_err());
+}
+
+#[test]
+fn board_orientation() {
+    use cte::chess::renderer::Renderer;
+
+    let mut board = BoardManager::new();
+    play_moves(&mut board, &["e2e4"]);
+    assert_eq!(board.orientation(), Orientation::White);
+    assert!(board.to_string().starts_with("   a b c d e f g h\n 8 ♜"));
+    let black_front = Renderer::Glyphs.render(&board, Orientation::Black);
+    assert!(black_front.contains("\n 1 ♖ ♘"));
+    assert_eq!(
+        Renderer::Glyphs.render(&board, Orientation::SideToMove),
+        black_front
+    );
+    assert_eq!(
+        Renderer::Letters
+            .render(&board, Orientation::Player(Color::Black))
+            .lines()
+            .nth(1),
+        Some(" 1 R N B Q K B N R")
+    );
+
+    assert_eq!(
+        Orientation::from_name("Side"),
+        Some(Orientation::SideToMove)
+    );
+    assert_eq!(
+        Orientation::from_name("player black"),
+        Some(Orientation::Player(Color::Black))
+    );
+    assert_eq!(Orientation::from_name("white black"), None);
+
+    // flipping the board that follows the side to move keeps the side at the top now
+    board.set_orientation(Orientation::SideToMove);
+    board.flip();
+    assert_eq!(board.orientation(), Orientation::White);
+    board.set_orientation(Orientation::Player(Color::White));
+    board.flip();
+    assert_eq!(board.orientation(), Orientation::Player(Color::Black));
+
+    let mut session = GameSession::new();
+    let output = session
+        .execute(parse_command("orientation black").unwrap())
+        .unwrap();
+    assert!(output.starts_with("Board Shown From black\n"));
+    assert!(output.contains("\n 1 ♖ ♘"));
+    session.execute(parse_command("e2e4").unwrap()).unwrap();
+    session.execute(parse_command("new").unwrap()).unwrap();
+    assert_eq!(session.board().orientation(), Orientation::Black);
+    assert_eq!(
+        session
+            .execute(parse_command("orientation").unwrap())
+            .unwrap()
+            .lines()
+            .nth(2),
+        Some(" *black")
+    );
+    session.execute(parse_command("flip").unwrap()).unwrap();
+    assert_eq!(session.board().orientation(), Orientation::White);
+    assert!(parse_command("orientation sideways").is_err());
+    assert_eq!(
+        cte::parse_game_options(&["--orientation".to_string(), "side".to_string()])
+            .unwrap()
+            .orientation,
+        Some(Orientation::SideToMove)
+    );
+}
+
+#[test]
+fn material_balance() {
+    use cte::chess::material::piece_points;
+    use cte::chess::piece::Piece;
+    use cte::cli::render::captures_text;
+
+    let mut board = BoardManager::new();
+    let material = board.material();
+    assert_eq!(material.white.count(PieceType::Pawn), 8);
+    assert_eq!(material.of(Color::Black).count(PieceType::Knight), 2);
+    assert_eq!(material.white.points(), 39);
+    assert_eq!(material.advantage(), None);
+    assert_eq!(captures_text(&board, Color::White), "");
+
+    play_moves(
+        &mut board,
+        &[
+            "e2e4", "d7d5", "e4d5", "d8d5", "b1c3", "d5a2", "c3b5", "a2b2", "c1b2",
+        ],
+    );
+    assert_eq!(
+        board.captured(Color::White),
+        vec![
+            Piece::new(PieceType::Pawn, Color::Black),
+            Piece::new(PieceType::Queen, Color::Black)
+        ]
+    );
+    assert_eq!(board.captured(Color::Black).len(), 3);
+    let material = board.material();
+    assert_eq!(material.balance(), 7);
+    assert_eq!(material.advantage(), Some((Color::White, 7)));
+    assert_eq!(captures_text(&board, Color::White), "♟♛ +7");
+    assert_eq!(captures_text(&board, Color::Black), "♙♙♙");
+
+    // the pieces in the pockets are still material
+    let board = BoardManager::try_from_variant_fen(
+        "4k3/8/8/8/8/8/8/4K3[Qp] w - - 0 1",
+        VariantKind::Crazyhouse,
+    )
+    .unwrap();
+    assert_eq!(board.material().advantage(), Some((Color::White, 8)));
+    assert_eq!(piece_points(PieceType::Archbishop), 8);
+
+    let mut session = GameSession::new();
+    for chess_move in ["e2e4", "d7d5", "e4d5"] {
+        session.execute(parse_command(chess_move).unwrap()).unwrap();
+    }
+    let output = session.execute(parse_command("flip").unwrap()).unwrap();
+    assert!(output.starts_with("   a b c d e f g h   ♟ +1\n"));
+    assert!(output.contains("\n   a b c d e f g h\nBlack To Move"));
+}
+
+#[test]
+fn svg_diagrams() {
+    use cte::chess::svg::{render_svg, DiagramOptions};
+
+    let mut board = BoardManager::new();
+    play_moves(&mut board, &["e2e4"]);
+    let svg = render_svg(&board, &DiagramOptions::default());
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="405" height="405""#));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<rect").count(), 65);
+    assert_eq!(svg.matches("<text").count(), 32 + 32);
+    // the white rook on a1 is in the bottom left corner
+    assert!(svg.contains(r#"<text x="45" y="360" font-family"#));
+
+    let options = DiagramOptions {
+        square_size: 40,
+        orientation: Orientation::Black,
+        coordinates: false,
+        highlights: vec![parse_square("e4").unwrap()],
+        arrows: vec![(parse_square("g8").unwrap(), parse_square("f6").unwrap())],
+    };
+    let svg = render_svg(&board, &options);
+    assert!(svg.contains(r#"width="320" height="320""#));
+    assert_eq!(svg.matches("<text").count(), 32);
+    assert_eq!(svg.matches(r#"fill-opacity="0.5""#).count(), 1);
+    assert_eq!(svg.matches("<line").count(), 1);
+    // with black at the bottom the a1 rook is in the top right corner
+    assert!(svg.contains(r#"<text x="300" y="20" font-family"#));
+
+    let board =
+        BoardManager::new_sized(chess::board_manager::BoardSizeInfo::from_name("10x8").unwrap());
+    assert!(render_svg(&board, &DiagramOptions::default()).contains(">C</text>"));
+
+    let file = std::env::temp_dir().join(format!("cte-diagram-{}.svg", std::process::id()));
+    let file_name = file.to_str().unwrap();
+    let mut session = GameSession::new();
+    session.execute(parse_command("e2e4").unwrap()).unwrap();
+    let output = session
+        .execute(parse_command(&format!("diagram {} d5 g1f3", file_name)).unwrap())
+        .unwrap();
+    assert_eq!(output, format!("Saved The Diagram To {}", file_name));
+    let svg = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    // the given square and the two squares of the last move
+    assert_eq!(svg.matches(r#"fill-opacity="0.5""#).count(), 3);
+    assert_eq!(svg.matches("<line").count(), 1);
+    assert!(parse_command("diagram").is_err());
+    assert!(parse_command("diagram out.svg z9").is_err());
+    // the tenth row of the bigger boards is a square, not a move
+    let square = |name: &str| parse_square(name).unwrap();
+    match parse_command("diagram out.svg a10 e4 a1a10").unwrap() {
+        Command::Diagram {
+            highlights, arrows, ..
+        } => {
+            assert_eq!(highlights, vec![square("a10"), square("e4")]);
+            assert_eq!(arrows, vec![(square("a1"), square("a10"))]);
+        }
+        _ => panic!("diagram is parsed as another command"),
+    }
+}
+
+#[test]
+fn pgn_save_and_resume() {
+    use cte::chess::error::PgnError;
+    use cte::chess::pgn::{move_to_san, parse_pgn, parse_san, write_pgn};
+    use cte::chess::renderer::Renderer;
+    use cte::cli::autosave::{resume_from, save_to};
+    use cte::cli::render::Theme;
+
+    let mut board = BoardManager::new_from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let san =
+        |board: &mut BoardManager, uci: &str| move_to_san(board, &parse_chess_move(uci).unwrap());
+    assert_eq!(san(&mut board, "e1g1"), "O-O");
+    assert_eq!(san(&mut board, "e1a1"), "O-O-O");
+    assert_eq!(san(&mut board, "b7a8q"), "bxa8=Q+");
+    assert_eq!(san(&mut board, "b7b8n"), "b8=N");
+    assert_eq!(san(&mut board, "a1a8"), "Rxa8+");
+    assert_eq!(san(&mut board, "h1h8"), "Rxh8+");
+    let mut board = BoardManager::new_from_fen("4k3/8/8/8/8/8/8/4KN1N w - - 0 1");
+    assert_eq!(san(&mut board, "h1g3"), "Nhg3");
+    assert_eq!(
+        parse_san(&mut board, "Nfg3+").map(|m| m.to_string()),
+        Some("f1g3".to_string())
+    );
+    assert_eq!(
+        parse_san(&mut board, "h1g3").map(|m| m.to_string()),
+        Some("h1g3".to_string())
+    );
+    assert!(parse_san(&mut board, "Ng3").is_none());
+
+    let text = "[Event \"Club \\\"Open\\\"\"]\n[Result \"1-0\"]\n\n\
+        1. e4 {best by test} e5 2.Nf3 (2. f4 exf4) Nc6 $1 3. Bb5 a6 ; the Morphy defence\n\
+        4. Bxc6 dxc6 5. O-O f6 1-0\n";
+    let game = parse_pgn(text).unwrap();
+    assert_eq!(game.tag("Event"), Some("Club \"Open\""));
+    assert_eq!(game.moves.len(), 10);
+    assert_eq!(game.result, "1-0");
+    let board = game.board().unwrap();
+    assert_eq!(
+        board.to_fen(),
+        "r1bqkbnr/1pp3pp/p1p2p2/4p3/4P3/5N2/PPPP1PPP/RNBQ1RK1 w kq - 0 6"
+    );
+    let written = write_pgn(&board, &[("White", "Morphy".to_string())], "1-0");
+    assert!(written.starts_with("[Event \"?\"]\n[Site \"?\"]\n"));
+    assert!(written.contains("[White \"Morphy\"]\n"));
+    assert!(written.ends_with("\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Bxc6 dxc6 5. O-O f6 1-0\n"));
+    assert_eq!(
+        parse_pgn("1. e4 e5 2. Ke3").unwrap().board().err(),
+        Some(PgnError::IllegalMove {
+            move_number: 2,
+            color: Color::White,
+            san: "Ke3".to_string()
+        })
+    );
+    assert_eq!(
+        parse_pgn("1. e4 {never closed").err(),
+        Some(PgnError::UnclosedComment)
+    );
+    // the untouched start of a variant needs no fen tag
+    for (variant, first_move) in [
+        (VariantKind::ThreeCheck, "e2e3"),
+        (VariantKind::Crazyhouse, "e2e3"),
+        (VariantKind::Antichess, "e2e3"),
+        (VariantKind::Horde, "e4e5"),
+    ] {
+        let mut board = BoardManager::new_variant(variant);
+        board
+            .handle_move(&parse_chess_move(first_move).unwrap())
+            .unwrap();
+        let written = write_pgn(&board, &[], "*");
+        assert!(!written.contains("[SetUp"), "{}", written);
+        assert_eq!(
+            parse_pgn(&written).unwrap().board().unwrap().to_fen(),
+            board.to_fen()
+        );
+    }
+
+    // castling written with zeros, with and without a move number stuck to it
+    let zeros = parse_pgn("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 Nf6 5.d3 0-0 *").unwrap();
+    assert_eq!(zeros.moves[6], "0-0");
+    assert_eq!(zeros.moves[9], "0-0");
+    assert_eq!(
+        zeros.board().unwrap().to_fen(),
+        "r1bq1rk1/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQ1RK1 w - - 1 6"
+    );
+    assert_eq!(
+        parse_pgn("1.0-0-0").unwrap().moves,
+        vec!["0-0-0".to_string()]
+    );
+
+    // a game from a position with black to move keeps its fen and its move numbers
+    let mut board = BoardManager::try_from_variant_fen(
+        "4k3/8/8/8/8/8/4p3/4K3[Q] b - - 0 30",
+        VariantKind::Crazyhouse,
+    )
+    .unwrap();
+    play_moves(&mut board, &["e8d7", "Q@d2"]);
+    let written = write_pgn(&board, &[], "*");
+    assert!(written.contains(
+        "[Variant \"Crazyhouse\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4p3/4K3[Q] b - - 0 30\"]\n"
+    ));
+    assert!(written.ends_with("\n\n30... Kd7 31. Q@d2+ *\n"));
+    assert_eq!(
+        parse_pgn(&written).unwrap().board().unwrap().to_fen(),
+        board.to_fen()
+    );
+
+    let mut session = GameSession::new();
+    for command in [
+        "e2e4",
+        "e7e5",
+        "pieces letters",
+        "orientation player black",
+        "theme classic",
+        "resign",
+    ] {
+        session.execute(parse_command(command).unwrap()).unwrap();
+    }
+    let file = std::env::temp_dir()
+        .join(format!("cte-resume-{}", std::process::id()))
+        .join("game.pgn");
+    save_to(&file, &session).unwrap();
+    let mut resumed = resume_from(&file).unwrap();
+    std::fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    assert_eq!(resumed.board().to_fen(), session.board().to_fen());
+    assert_eq!(resumed.board().renderer(), Renderer::Letters);
+    assert_eq!(
+        resumed.board().orientation(),
+        Orientation::Player(Color::Black)
+    );
+    assert_eq!(resumed.theme(), Theme::Classic);
+    assert_eq!(
+        resumed.result(),
+        Some(GameResult::Resignation {
+            winner: Color::Black
+        })
+    );
+    assert!(
+        resumed.clocks().used(Color::White, Color::White)
+            >= session.clocks().used(Color::White, Color::Black)
+    );
+    resumed.execute(Command::Undo).unwrap();
+    assert_eq!(resumed.board().history().len(), 1);
+    assert!(resume_from(&file).is_err());
+
+    assert!(
+        cte::parse_game_options(&["--resume".to_string()])
+            .unwrap()
+            .resume
+    );
+}
+
+#[test]
+fn batch_mode() {
+    use cte::cli::batch::{run_batch, Report, EXIT_ILLEGAL_MOVE, EXIT_SUCCESS, EXIT_USAGE};
+    use cte::parse_game_options;
+
+    let run = |args: &str, input: &str| {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        let options = parse_game_options(&args).unwrap();
+        assert!(options.is_batch());
+        let mut output = Vec::new();
+        let exit_code = run_batch(&options, &mut input.as_bytes(), &mut output);
+        (exit_code, String::from_utf8(output).unwrap())
+    };
+
+    assert_eq!(
+        run("--moves e2e4,e7e5,Nf3", ""),
+        (
+            EXIT_SUCCESS,
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2\n".to_string()
+        )
+    );
+    let (exit_code, output) = run(
+        "--fen 6k1/5ppp/8/8/8/8/8/R5K1 --moves a1a8 --print status,legal",
+        "",
+    );
+    assert_eq!(exit_code, EXIT_SUCCESS);
+    assert_eq!(output, "Checkmate, White Wins\n\n");
+    let (exit_code, output) = run("--moves e2e4 --moves e2e4,d7d5 --print board", "");
+    assert_eq!(exit_code, EXIT_ILLEGAL_MOVE);
+    assert!(output.contains(" 4 · · · · ♙ · · ·\n"));
+    assert!(output.contains(" 7 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟\n"));
+    assert_eq!(run("--fen 8/8 --print fen", "").0, EXIT_USAGE);
+    assert_eq!(
+        run("--script /no/such/script --print fen", "").0,
+        EXIT_USAGE
+    );
+
+    let script = "# the scandinavian\ne2e4\nd7d5\n\nfen\nquit\ne4d5\n";
+    let (exit_code, output) = run("--script - --pieces letters --print status", script);
+    assert_eq!(exit_code, EXIT_SUCCESS);
+    assert!(output.contains(
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2\nWhite To Move\n"
+    ));
+    assert!(output.contains(" 2 P P P P . P P P\n"));
+    let (exit_code, output) = run("--script -", "e2e4\nundo\nundo\ne2e4\n");
+    assert_eq!(exit_code, EXIT_ILLEGAL_MOVE);
+    assert!(output.ends_with("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n"));
+
+    assert_eq!(
+        parse_game_options(&["--print".to_string(), "fen,legal".to_string()])
+            .unwrap()
+            .reports,
+        vec![Report::Fen, Report::Legal]
+    );
+    assert!(parse_game_options(&["--print".to_string(), "moves".to_string()]).is_err());
+    assert!(!parse_game_options(&[]).unwrap().is_batch());
+}
+
+#[test]
+fn subcommands() {
+    use cte::chess::perft::divide;
+    use cte::cli::clock::TimeControl;
+    use cte::cli::puzzle::{builtin_puzzles, parse_puzzles, run_puzzles};
+    use cte::cli::subcommand::*;
+    use cte::GameOptions;
+    use std::time::Duration;
+
+    let args = |line: &str| {
+        line.split_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(
+        parse_subcommand(&args("perft 3 --divide")),
+        Ok(Subcommand::Perft(PerftOptions {
+            depth: 3,
+            divide: true,
+            ..PerftOptions::default()
+        }))
+    );
+    assert_eq!(
+        parse_subcommand(&args("pgn --help")),
+        Ok(Subcommand::Help(PGN_USAGE))
+    );
+    assert_eq!(
+        parse_subcommand(&args("")),
+        Ok(Subcommand::Play(GameOptions::default()))
+    );
+    assert_eq!(
+        parse_subcommand(&args("play --mode koth --time 5+3")),
+        Ok(Subcommand::Play(GameOptions {
+            mode: Some(VariantKind::KingOfTheHill),
+            time_control: Some(TimeControl {
+                initial: Duration::from_secs(300),
+                increment: Duration::from_secs(3),
+            }),
+            ..GameOptions::default()
+        }))
+    );
+    assert_eq!(
+        parse_subcommand(&args("pgn - --to san")),
+        Ok(Subcommand::Pgn(PgnOptions {
+            file: "-".to_string(),
+            format: PgnFormat::San,
+            validate: false,
+        }))
+    );
+    for line in [
+        "perft",
+        "perft 0",
+        "analyze --depth",
+        "pgn --to yaml -",
+        "puzzle --number 0",
+        "castle",
+    ] {
+        assert!(parse_subcommand(&args(line)).is_err(), "{}", line);
+    }
+    assert!(parse_subcommand(&args("play --resume --mode atomic")).is_err());
+
+    let mut board = BoardManager::new();
+    assert_eq!(perft(&mut board, 3), 8902);
+    let counts = divide(&mut board, 2);
+    assert_eq!(counts.len(), 20);
+    assert!(counts.iter().all(|(_, nodes)| *nodes == 20));
+    let mut output = Vec::new();
+    let options = PerftOptions {
+        depth: 2,
+        fen: Some("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1".to_string()),
+        ..PerftOptions::default()
+    };
+    assert_eq!(run_perft(&options, &mut output), 0);
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("Nodes: 191\n"));
+
+    let mut output = Vec::new();
+    let options = AnalyzeOptions {
+        moves: vec!["f3".to_string(), "e5".to_string(), "g4".to_string()],
+        depth: Some(2),
+        ..AnalyzeOptions::default()
+    };
+    assert_eq!(run_analyze(&options, &mut output), 0);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Best Move: Qh4# (d8h4)\nEvaluation: Black Mates\n"));
+
+    let pgn = "[White \"A\"]\n\n1. e4 e5 2. Nf3 {a comment} Nc6 3. Bb5 a6 (3... Nf6) 4. O-O 1-0\n";
+    let convert = |format, validate, pgn: &str| {
+        let options = PgnOptions {
+            file: "-".to_string(),
+            format,
+            validate,
+        };
+        let mut output = Vec::new();
+        let exit_code = run_pgn(&options, &mut pgn.as_bytes(), &mut output);
+        (exit_code, String::from_utf8(output).unwrap())
+    };
+    assert_eq!(
+        convert(PgnFormat::Uci, false, pgn),
+        (0, "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 e1g1\n".to_string())
+    );
+    assert_eq!(
+        convert(PgnFormat::San, false, pgn).1,
+        "e4 e5 Nf3 Nc6 Bb5 a6 O-O\n"
+    );
+    assert!(convert(PgnFormat::Pgn, false, pgn)
+        .1
+        .ends_with("[White \"A\"]\n[Black \"?\"]\n[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. O-O 1-0\n"));
+    assert_eq!(
+        convert(PgnFormat::Pgn, true, pgn).1,
+        "Valid, 7 Half Moves, Result 1-0\n"
+    );
+    assert_eq!(
+        convert(PgnFormat::Pgn, true, &pgn.replace("Bb5", "Bb6")).0,
+        1
+    );
+
+    for puzzle in builtin_puzzles() {
+        let mut board = BoardManager::new_from_fen(&puzzle.fen);
+        for uci in &puzzle.solution {
+            board.handle_move(&parse_chess_move(uci).unwrap()).unwrap();
+        }
+        assert!(
+            matches!(board.game_status(), GameStatus::Checkmate { .. }),
+            "{}",
+            puzzle.title
+        );
+    }
+    assert!(parse_puzzles("6k1/8/8/8/8/8/8/R5K1 w - - 0 1;a1a8").is_err());
+    let mut output = Vec::new();
+    let answers = "a1a7\nhint\nRa8\nsolution\nb8b1\nskip\nQg8+\nNf7\n";
+    let options = PuzzleOptions::default();
+    assert_eq!(
+        run_puzzles(&options, &mut answers.as_bytes(), &mut output),
+        0
+    );
+    let output = String::from_utf8(output).unwrap();
+    for line in [
+        "Ra7 Is Not The Move, Try Again",
+        "Hint: The Move Starts On a1",
+        "Ra8# Solves It",
+        "Solution: Qxf7#",
+        "Rb1# Solves It",
+        "Qg8+ Is Right, Black Replies Rxg8",
+        "Nf7# Solves It",
+        "Solved 3 Of 5 Puzzles",
+    ] {
+        assert!(output.contains(line), "{}", line);
+    }
+}
+
+#[test]
+fn time_controls() {
+    use cte::cli::clock::{Clocks, TimeControl};
+    use std::time::Duration;
+
+    let time_control = TimeControl::from_name("0.5+2").unwrap();
+    assert_eq!(time_control.initial, Duration::from_secs(30));
+    assert_eq!(time_control.to_pgn_tag(), "30+2");
+    assert_eq!(TimeControl::from_pgn_tag("30+2"), Some(time_control));
+    assert_eq!(TimeControl::from_name("10").unwrap().to_string(), "10+0");
+    assert!(TimeControl::from_name("0").is_none());
+    assert!(TimeControl::from_name("5+x").is_none());
+
+    let mut clocks = Clocks::with_used(Duration::from_secs(40), Duration::ZERO);
+    assert_eq!(clocks.remaining(Color::White, Color::Black), None);
+    clocks.set_time_control(time_control, 6, 5);
+    assert_eq!(
+        clocks.remaining(Color::White, Color::Black),
+        Some(Duration::from_secs(2))
+    );
+    assert!(!clocks.is_out_of_time(Color::White, Color::Black));
+    clocks.end_turn(Color::Black);
+    assert!(clocks.remaining(Color::Black, Color::White).unwrap() > Duration::from_secs(41));
+
+    let mut session = GameSession::new();
+    session.set_time_control(TimeControl::from_name("1").unwrap());
+    session.execute(parse_command("e2e4").unwrap()).unwrap();
+    let pgn = session.to_pgn();
+    assert!(pgn.contains("[TimeControl \"60+0\"]"));
+    let resumed = GameSession::from_pgn(&pgn).unwrap();
+    assert_eq!(
+        resumed.clocks().time_control(),
+        session.clocks().time_control()
+    );
+
+    let pgn = pgn
+        .replace("WhiteTimeUsed \"0", "WhiteTimeUsed \"99")
+        .replace(" *", " 0-1");
+    let resumed = GameSession::from_pgn(&pgn).unwrap();
+    assert_eq!(
+        resumed.result(),
+        Some(GameResult::OutOfTime {
+            winner: Color::Black
+        })
+    );
+}